@@ -0,0 +1,279 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::LocalPlayers;
+
+/// Logical inputs that players can bind to any number of keys and gamepad buttons
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum InputAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Jump,
+    Grab,
+    Yell,
+    Attack,
+    Pause,
+    Back,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 10] = [
+        InputAction::Up,
+        InputAction::Down,
+        InputAction::Left,
+        InputAction::Right,
+        InputAction::Jump,
+        InputAction::Grab,
+        InputAction::Yell,
+        InputAction::Attack,
+        InputAction::Pause,
+        InputAction::Back,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            InputAction::Up => "Up",
+            InputAction::Down => "Down",
+            InputAction::Left => "Left",
+            InputAction::Right => "Right",
+            InputAction::Jump => "Jump",
+            InputAction::Grab => "Grab",
+            InputAction::Yell => "Yell",
+            InputAction::Attack => "Attack",
+            InputAction::Pause => "Pause",
+            InputAction::Back => "Back",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Binding {
+    Key(KeyCode),
+    Button(GamepadButtonType),
+}
+
+impl Binding {
+    pub fn name(&self) -> String {
+        match self {
+            Binding::Key(key) => format!("{:?}", key),
+            Binding::Button(button) => format!("{:?}", button),
+        }
+    }
+
+    fn pressed(
+        &self,
+        keyboard_input: &Input<KeyCode>,
+        gamepad_buttons: &Input<GamepadButton>,
+        gamepad: Option<Gamepad>,
+    ) -> bool {
+        match self {
+            Binding::Key(key) => keyboard_input.pressed(*key),
+            Binding::Button(button) => gamepad
+                .is_some_and(|gamepad| gamepad_buttons.pressed(GamepadButton::new(gamepad, *button))),
+        }
+    }
+
+    fn just_pressed(
+        &self,
+        keyboard_input: &Input<KeyCode>,
+        gamepad_buttons: &Input<GamepadButton>,
+        gamepad: Option<Gamepad>,
+    ) -> bool {
+        match self {
+            Binding::Key(key) => keyboard_input.just_pressed(*key),
+            Binding::Button(button) => gamepad.is_some_and(|gamepad| {
+                gamepad_buttons.just_pressed(GamepadButton::new(gamepad, *button))
+            }),
+        }
+    }
+}
+
+/// Physical bindings for each logical action, one map per player.
+/// Keys work for whichever player binds them, gamepad buttons are only read from that player's gamepad.
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct InputMap {
+    pub players: Vec<BTreeMap<InputAction, Vec<Binding>>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        use Binding::{Button, Key};
+
+        let player_one = BTreeMap::from([
+            (
+                InputAction::Up,
+                vec![Key(KeyCode::W), Key(KeyCode::Up), Button(GamepadButtonType::DPadUp)],
+            ),
+            (
+                InputAction::Down,
+                vec![Key(KeyCode::S), Key(KeyCode::Down), Button(GamepadButtonType::DPadDown)],
+            ),
+            (
+                InputAction::Left,
+                vec![Key(KeyCode::A), Key(KeyCode::Left), Button(GamepadButtonType::DPadLeft)],
+            ),
+            (
+                InputAction::Right,
+                vec![Key(KeyCode::D), Key(KeyCode::Right), Button(GamepadButtonType::DPadRight)],
+            ),
+            (
+                InputAction::Jump,
+                vec![
+                    Key(KeyCode::Space),
+                    Key(KeyCode::W),
+                    Key(KeyCode::Up),
+                    Button(GamepadButtonType::South),
+                ],
+            ),
+            (
+                InputAction::Grab,
+                vec![
+                    Key(KeyCode::Q),
+                    Key(KeyCode::E),
+                    Button(GamepadButtonType::West),
+                    Button(GamepadButtonType::East),
+                ],
+            ),
+            (
+                InputAction::Yell,
+                vec![Key(KeyCode::F), Button(GamepadButtonType::North)],
+            ),
+            (
+                InputAction::Attack,
+                vec![Key(KeyCode::X), Button(GamepadButtonType::RightTrigger)],
+            ),
+            (
+                InputAction::Pause,
+                vec![Key(KeyCode::Escape), Button(GamepadButtonType::Start)],
+            ),
+            (
+                InputAction::Back,
+                vec![Key(KeyCode::Escape), Button(GamepadButtonType::East)],
+            ),
+        ]);
+
+        // Player two can also grab from the keyboard
+        let mut player_two = gamepad_bindings();
+        player_two.insert(
+            InputAction::Grab,
+            vec![
+                Key(KeyCode::M),
+                Button(GamepadButtonType::West),
+                Button(GamepadButtonType::East),
+            ],
+        );
+        player_two.insert(
+            InputAction::Yell,
+            vec![Key(KeyCode::N), Button(GamepadButtonType::North)],
+        );
+        player_two.insert(
+            InputAction::Attack,
+            vec![Key(KeyCode::B), Button(GamepadButtonType::RightTrigger)],
+        );
+
+        InputMap {
+            players: vec![player_one, player_two],
+        }
+    }
+}
+
+impl InputMap {
+    pub fn bindings(&self, player: usize, action: InputAction) -> &[Binding] {
+        self.players
+            .get(player)
+            .and_then(|bindings| bindings.get(&action))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    pub fn pressed(
+        &self,
+        player: usize,
+        action: InputAction,
+        keyboard_input: &Input<KeyCode>,
+        gamepad_buttons: &Input<GamepadButton>,
+        gamepad: Option<Gamepad>,
+    ) -> bool {
+        self.bindings(player, action)
+            .iter()
+            .any(|binding| binding.pressed(keyboard_input, gamepad_buttons, gamepad))
+    }
+
+    pub fn just_pressed(
+        &self,
+        player: usize,
+        action: InputAction,
+        keyboard_input: &Input<KeyCode>,
+        gamepad_buttons: &Input<GamepadButton>,
+        gamepad: Option<Gamepad>,
+    ) -> bool {
+        self.bindings(player, action)
+            .iter()
+            .any(|binding| binding.just_pressed(keyboard_input, gamepad_buttons, gamepad))
+    }
+
+    /// True when any joined player has just pressed one of their bindings for the action
+    pub fn any_just_pressed(
+        &self,
+        action: InputAction,
+        keyboard_input: &Input<KeyCode>,
+        gamepad_buttons: &Input<GamepadButton>,
+        local_players: &LocalPlayers,
+    ) -> bool {
+        local_players.joined().any(|player| {
+            self.just_pressed(
+                player,
+                action,
+                keyboard_input,
+                gamepad_buttons,
+                local_players.gamepad(player),
+            )
+        })
+    }
+
+    /// Gives players past the end of the map the default gamepad layout
+    pub fn add_gamepad_player(&mut self, player: usize) {
+        if self.players.len() <= player {
+            self.players.resize_with(player + 1, gamepad_bindings);
+        }
+    }
+
+    /// Replaces the action's binding in `slot`, or adds another binding when `slot` is past the end.
+    /// The action's other bindings are kept.
+    pub fn rebind(&mut self, player: usize, action: InputAction, slot: usize, binding: Binding) {
+        if self.players.len() <= player {
+            self.players.resize_with(player + 1, BTreeMap::new);
+        }
+
+        let bindings = self.players[player].entry(action).or_default();
+        match bindings.get_mut(slot) {
+            Some(existing) => *existing = binding,
+            None => bindings.push(binding),
+        }
+    }
+}
+
+fn gamepad_bindings() -> BTreeMap<InputAction, Vec<Binding>> {
+    use Binding::Button;
+
+    BTreeMap::from([
+        (InputAction::Up, vec![Button(GamepadButtonType::DPadUp)]),
+        (InputAction::Down, vec![Button(GamepadButtonType::DPadDown)]),
+        (InputAction::Left, vec![Button(GamepadButtonType::DPadLeft)]),
+        (InputAction::Right, vec![Button(GamepadButtonType::DPadRight)]),
+        (InputAction::Jump, vec![Button(GamepadButtonType::South)]),
+        (
+            InputAction::Grab,
+            vec![Button(GamepadButtonType::West), Button(GamepadButtonType::East)],
+        ),
+        (InputAction::Yell, vec![Button(GamepadButtonType::North)]),
+        (InputAction::Attack, vec![Button(GamepadButtonType::RightTrigger)]),
+        (InputAction::Pause, vec![Button(GamepadButtonType::Start)]),
+        (InputAction::Back, vec![Button(GamepadButtonType::East)]),
+    ])
+}
@@ -0,0 +1,104 @@
+use bevy::input::gamepad::{GamepadConnection, GamepadConnectionEvent};
+use bevy::prelude::*;
+
+use super::InputMap;
+
+/// Players currently taking part, indexed the same as `Player::index` and `InputMap::players`.
+/// The first player is always present on the keyboard, every other slot is held by a gamepad.
+#[derive(Resource, Debug)]
+pub struct LocalPlayers {
+    pub slots: Vec<Option<PlayerSlot>>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlayerSlot {
+    pub gamepad: Option<Gamepad>,
+}
+
+impl Default for LocalPlayers {
+    fn default() -> Self {
+        LocalPlayers {
+            slots: vec![Some(PlayerSlot::default())],
+        }
+    }
+}
+
+impl LocalPlayers {
+    pub fn is_joined(&self, player: usize) -> bool {
+        matches!(self.slots.get(player), Some(Some(_)))
+    }
+
+    pub fn gamepad(&self, player: usize) -> Option<Gamepad> {
+        self.slots.get(player).copied().flatten().and_then(|slot| slot.gamepad)
+    }
+
+    pub fn joined(&self) -> impl Iterator<Item = usize> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| slot.map(|_| index))
+    }
+
+    /// Hands the gamepad to the keyboard player if they don't have one yet,
+    /// otherwise to the first free slot
+    fn join(&mut self, gamepad: Gamepad) -> usize {
+        if let Some(first) = self.slots[0].as_mut() {
+            if first.gamepad.is_none() {
+                first.gamepad = Some(gamepad);
+                return 0;
+            }
+        }
+
+        let slot = Some(PlayerSlot {
+            gamepad: Some(gamepad),
+        });
+
+        match self.slots.iter().position(Option::is_none) {
+            Some(index) => {
+                self.slots[index] = slot;
+                index
+            }
+            None => {
+                self.slots.push(slot);
+                self.slots.len() - 1
+            }
+        }
+    }
+
+    fn leave(&mut self, gamepad: Gamepad) {
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if slot.is_some_and(|slot| slot.gamepad == Some(gamepad)) {
+                // The keyboard player never leaves, they just lose their gamepad
+                *slot = if index == 0 {
+                    Some(PlayerSlot::default())
+                } else {
+                    None
+                };
+            }
+        }
+
+        while self.slots.len() > 1 && self.slots.last().is_some_and(Option::is_none) {
+            self.slots.pop();
+        }
+    }
+}
+
+pub fn gamepad_connections(
+    mut connection_events: EventReader<GamepadConnectionEvent>,
+    mut local_players: ResMut<LocalPlayers>,
+    mut input_map: ResMut<InputMap>,
+) {
+    for event in connection_events.iter() {
+        match event.connection {
+            GamepadConnection::Connected(_) => {
+                let player = local_players.join(event.gamepad);
+                input_map.add_gamepad_player(player);
+                info!("Gamepad {} joined as player {}", event.gamepad.id, player + 1);
+            }
+            GamepadConnection::Disconnected => {
+                local_players.leave(event.gamepad);
+                info!("Gamepad {} left", event.gamepad.id);
+            }
+        }
+    }
+}
@@ -1,10 +1,15 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::actions::game_control::{get_movement, GameControl};
+use crate::settings::Settings;
 
-use self::game_control::get_gamepad_movement;
+pub use self::input_map::{Binding, InputAction, InputMap};
+pub use self::local_players::{LocalPlayers, PlayerSlot};
 
-mod game_control;
+use self::local_players::gamepad_connections;
+
+mod input_map;
+mod local_players;
 
 pub struct ActionsPlugin;
 
@@ -12,112 +17,212 @@ pub struct ActionsPlugin;
 // Actions can then be used as a resource in other systems to act on the player input.
 impl Plugin for ActionsPlugin {
     fn build(&self, app: &mut App) {
+        let input_map = app
+            .world
+            .get_resource::<Settings>()
+            .map(|settings| settings.controls.clone())
+            .unwrap_or_default();
+
         app.init_resource::<Actions>()
+            .init_resource::<LocalPlayers>()
+            .insert_resource(input_map)
+            .add_systems(Update, gamepad_connections.before(set_movement_actions))
             .add_systems(Update, set_movement_actions)
             .add_systems(Update, set_pause_actions)
             .add_systems(Update, set_back_actions)
+            .add_systems(Update, set_menu_actions)
+            .add_systems(Update, store_input_map.run_if(resource_changed::<InputMap>()))
         ;
     }
 }
 
 #[derive(Default, Resource)]
 pub struct Actions {
-    pub player_movement: [Vec2; 2],
-    pub jump: [bool; 2],
-    pub action: [bool; 2],
+    /// One entry per `LocalPlayers` slot or `InputMap` player, whichever there are more of.
+    /// Players without a gamepad only get their keyboard bindings.
+    pub players: Vec<PlayerActions>,
     pub pause: bool,
     pub back: bool,
+    pub menu_movement: IVec2,
+    pub confirm: bool,
+}
+
+/// Presses are latched until the simulation reads them with `Actions::take_player`,
+/// so a tap between two fixed ticks is never lost or seen twice
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub struct PlayerActions {
+    pub movement: Vec2,
+    pub jump: bool,
+    pub jump_pressed: bool,
+    pub action: bool,
+    /// Older replays were recorded before yelling and attacking existed
+    #[serde(default)]
+    pub yell: bool,
+    #[serde(default)]
+    pub attack: bool,
+}
+
+impl Actions {
+    pub fn player(&self, index: usize) -> PlayerActions {
+        self.players.get(index).copied().unwrap_or_default()
+    }
+
+    /// Reads a player's actions for a simulation tick, clearing the latched presses
+    pub fn take_player(&mut self, index: usize) -> PlayerActions {
+        let player_actions = self.player(index);
+
+        if let Some(latched) = self.players.get_mut(index) {
+            latched.jump_pressed = false;
+            latched.action = false;
+            latched.yell = false;
+            latched.attack = false;
+        }
+
+        player_actions
+    }
 }
 
 pub fn set_pause_actions(
     mut actions: ResMut<Actions>,
+    input_map: Res<InputMap>,
     keyboard_input: Res<Input<KeyCode>>,
-    gamepad_input: Res<Gamepads>,
+    local_players: Res<LocalPlayers>,
     gamepad_buttons: Res<Input<GamepadButton>>,
 ) {
-    actions.pause = keyboard_input.just_pressed(KeyCode::Escape);
-
-    for gamepad in gamepad_input.iter() {
-        if actions.pause {
-            break;
-        }
-
-        actions.pause =
-            gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::Start));
-    }
+    actions.pause = input_map.any_just_pressed(
+        InputAction::Pause,
+        &keyboard_input,
+        &gamepad_buttons,
+        &local_players,
+    );
 }
 
 pub fn set_back_actions(
     mut actions: ResMut<Actions>,
+    input_map: Res<InputMap>,
     keyboard_input: Res<Input<KeyCode>>,
-    gamepad_input: Res<Gamepads>,
+    local_players: Res<LocalPlayers>,
     gamepad_buttons: Res<Input<GamepadButton>>,
 ) {
-    actions.back = keyboard_input.just_pressed(KeyCode::Escape);
-
-    for gamepad in gamepad_input.iter() {
-        if actions.back {
-            break;
-        }
-
-        actions.back =
-            gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::East));
-    }
+    actions.back = input_map.any_just_pressed(
+        InputAction::Back,
+        &keyboard_input,
+        &gamepad_buttons,
+        &local_players,
+    );
 }
 
-pub fn set_movement_actions(
+pub fn set_menu_actions(
     mut actions: ResMut<Actions>,
+    input_map: Res<InputMap>,
+    local_players: Res<LocalPlayers>,
     keyboard_input: Res<Input<KeyCode>>,
     gamepad_input: Res<Gamepads>,
     gamepad_buttons: Res<Input<GamepadButton>>,
     gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut stick_held: Local<bool>,
 ) {
-    let gamepad_movement = get_gamepad_movement(&gamepad_input, &gamepad_buttons, &gamepad_axes);
+    let step = |action| -> i32 {
+        input_map.any_just_pressed(action, &keyboard_input, &gamepad_buttons, &local_players) as i32
+    };
 
-    let keyboard_movement = Vec2::new(
-        get_movement(GameControl::Right, &keyboard_input)
-            - get_movement(GameControl::Left, &keyboard_input),
-        get_movement(GameControl::Up, &keyboard_input)
-            - get_movement(GameControl::Down, &keyboard_input),
+    let mut menu_movement = IVec2::new(
+        step(InputAction::Right) - step(InputAction::Left),
+        step(InputAction::Up) - step(InputAction::Down),
     );
 
-    if keyboard_movement != Vec2::ZERO {
-        actions.player_movement[0] = keyboard_movement.normalize();
-    } else if gamepad_movement[0].length_squared() > 0.1 {
-        actions.player_movement[0] = gamepad_movement[0].normalize();
-    } else {
-        actions.player_movement[0] = Vec2::ZERO;
+    // Confirm is left unbindable so the menus can always be used to fix broken bindings
+    actions.confirm = keyboard_input.just_pressed(KeyCode::Return)
+        || keyboard_input.just_pressed(KeyCode::Space);
+
+    let mut stick = Vec2::ZERO;
+    for gamepad in gamepad_input.iter() {
+        actions.confirm = actions.confirm
+            || gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South));
+
+        stick += Vec2::new(
+            axes_value(&gamepad_axes, gamepad, GamepadAxisType::LeftStickX),
+            axes_value(&gamepad_axes, gamepad, GamepadAxisType::LeftStickY),
+        );
     }
-    
-    if gamepad_movement[1].length_squared() > 0.1 {
-        actions.player_movement[1] = gamepad_movement[1].normalize();
+
+    // The stick only counts as a single step until it is returned to the center
+    if stick.length() > 0.5 {
+        if !*stick_held {
+            if stick.x.abs() > stick.y.abs() {
+                menu_movement.x += stick.x.signum() as i32;
+            } else {
+                menu_movement.y += stick.y.signum() as i32;
+            }
+        }
+        *stick_held = true;
     } else {
-        actions.player_movement[1] = Vec2::ZERO;
+        *stick_held = false;
     }
 
-    actions.jump[0] = keyboard_input.pressed(KeyCode::Space) || keyboard_movement.y > 0.;
-    actions.jump[1] = false;
+    actions.menu_movement = menu_movement.clamp(IVec2::NEG_ONE, IVec2::ONE);
+}
 
-    for gamepad in gamepad_input.iter() {
-        
-        if gamepad.id > 1 || actions.jump[gamepad.id] {
-            break;
+fn axes_value(axes: &Res<Axis<GamepadAxis>>, gamepad: Gamepad, axis_type: GamepadAxisType) -> f32 {
+    axes.get(GamepadAxis::new(gamepad, axis_type)).unwrap_or(0.)
+}
+
+pub fn set_movement_actions(
+    mut actions: ResMut<Actions>,
+    input_map: Res<InputMap>,
+    local_players: Res<LocalPlayers>,
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+) {
+    // Players without a gamepad can still be played from the keyboard, like a second player
+    // placed by the level on their default keys
+    let count = local_players.slots.len().max(input_map.players.len());
+    let mut players = Vec::with_capacity(count);
+
+    for player in 0..count {
+        let latched = actions.player(player);
+
+        let gamepad = local_players.gamepad(player);
+        let pressed = |action| {
+            input_map.pressed(player, action, &keyboard_input, &gamepad_buttons, gamepad)
+        };
+        let axis = |positive, negative| pressed(positive) as i32 as f32 - pressed(negative) as i32 as f32;
+
+        let mut movement = Vec2::new(
+            axis(InputAction::Right, InputAction::Left),
+            axis(InputAction::Up, InputAction::Down),
+        );
+
+        if let Some(gamepad) = gamepad {
+            movement.x += axes_value(&gamepad_axes, gamepad, GamepadAxisType::LeftStickX);
         }
-        actions.jump[gamepad.id] = actions.jump[gamepad.id]
-            || gamepad_buttons.pressed(GamepadButton::new(gamepad, GamepadButtonType::South));
+
+        let just_pressed = |action| {
+            input_map.just_pressed(player, action, &keyboard_input, &gamepad_buttons, gamepad)
+        };
+
+        players.push(PlayerActions {
+            movement: if movement.length_squared() > 0.1 {
+                movement.normalize()
+            } else {
+                Vec2::ZERO
+            },
+            jump: pressed(InputAction::Jump),
+            jump_pressed: latched.jump_pressed || just_pressed(InputAction::Jump),
+            action: latched.action || just_pressed(InputAction::Grab),
+            yell: latched.yell || just_pressed(InputAction::Yell),
+            attack: latched.attack || just_pressed(InputAction::Attack),
+        });
     }
 
-    actions.action[0] = keyboard_input.just_pressed(KeyCode::Q)
-        || keyboard_input.just_pressed(KeyCode::E);
-    
-    actions.action[1] = keyboard_input.just_pressed(KeyCode::M);
+    actions.players = players;
+}
 
-    for gamepad in gamepad_input.iter() {
-        if gamepad.id > 1 || actions.action[gamepad.id] {
-            break;
-        }
-        actions.action[gamepad.id] = actions.action[gamepad.id]
-            || gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::West))
-            || gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::East));
+/// Keeps the saved settings in step with any rebinding done in game
+fn store_input_map(input_map: Res<InputMap>, mut settings: ResMut<Settings>) {
+    if settings.controls != *input_map {
+        settings.controls = input_map.clone();
+        settings.save();
     }
 }
@@ -1,12 +1,18 @@
 use crate::{
+    fixed_step::SimulationSet,
     pickup::{check_for_pickups, PickupCollector, PickupEvent},
+    settings::Settings,
     sprite_anim::SpriteAnimator,
+    GameState,
 };
-use bevy::{prelude::*, sprite::Anchor};
+use bevy::{prelude::*, sprite::Anchor, utils::HashMap};
 use bevy_rapier2d::prelude::*;
 
 pub struct ActorPlugin;
 
+/// Tallest carry stack followed, anything taller must be a loop
+const MAX_CARRY_STACK: usize = 16;
+
 #[derive(Component, Clone)]
 pub struct Actor {
     pub move_speed: f32,
@@ -16,17 +22,35 @@ pub struct Actor {
     pub up_gravity: f32,
     pub down_gravity: f32,
     pub jump_speed: f32,
+    /// Longest time a held jump keeps rising at `jump_speed`
     pub jump_time: f32,
+    /// Upward speed is multiplied by this when jump is released early
+    pub jump_cut: f32,
+    /// How long after walking off a ledge a jump is still allowed
+    pub coyote_time: f32,
+    /// How long a jump pressed too early is remembered for
+    pub jump_buffer_time: f32,
+    pub wall_slide_speed: f32,
+    pub wall_jump_speed: f32,
+    pub wall_jump_kick: f32,
+    pub wall_jump_lock_time: f32,
+    /// How long a hit takes control away for
+    pub hitstun_time: f32,
     pub move_input: f32,
+    /// Direction held, including up and down, for aiming throws
+    pub aim_input: Vec2,
     pub jump_input: bool,
     pub grab_input: bool,
-    pub can_jump: bool,
+    pub yell_input: bool,
+    pub attack_input: bool,
 }
 
 #[derive(Component, Default, Clone)]
 pub struct Carrier {
     pub carrying: Option<Entity>,
     pub carry_offset: Vec3,
+    /// Sideways and upward speed things are thrown at
+    pub throw_speed: Vec2,
 }
 
 #[derive(Component, Clone)]
@@ -46,10 +70,43 @@ pub struct ActorStatus {
     pub air_timer: f32,
     pub left_wall: bool,
     pub right_wall: bool,
-    pub event: Option<ActorEvent>,
+    /// What the wall on each side is, when it is an entity of its own like a block
+    pub left_wall_entity: Option<Entity>,
+    pub right_wall_entity: Option<Entity>,
     pub last_dt: f32,
     pub carried: bool,
     pub carrying: bool,
+    pub wall_sliding: bool,
+    /// Time left before movement input takes over from a wall jump's kick
+    pub wall_jump_lock: f32,
+    pub jump_held: bool,
+    pub jump_state: JumpState,
+    /// Time since a jump press that hasn't launched yet
+    pub jump_buffer: Option<f32>,
+    /// Velocity to take on at the next tick from being hit
+    pub knockback: Option<Vec2>,
+    /// Time left before movement input works again after a hit
+    pub hitstun: f32,
+    /// Velocity to take on at the next tick from being thrown
+    pub launch: Option<Vec2>,
+    /// Flying from a throw, without control until landing or hitting a wall
+    pub thrown: bool,
+    /// Speed of the block being pushed, which holds the actor back to the same pace
+    pub pushing: Option<f32>,
+    /// Time left on the current yell
+    pub yelling: f32,
+    /// Time left on the current swing of a melee attack
+    pub attacking: f32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum JumpState {
+    #[default]
+    Grounded,
+    /// Rising under a held jump, with the time spent rising so far
+    Ascending(f32),
+    /// In the air, `jumped` is false after walking off a ledge so coyote time still applies
+    Falling { jumped: bool },
 }
 
 #[derive(Component, Default, Clone)]
@@ -62,6 +119,8 @@ pub struct ActorAnimationStates {
     pub run_carry_row: usize,
     pub push_row: usize,
     pub yell_row: usize,
+    pub wall_slide_row: usize,
+    pub wall_jump_row: usize,
 }
 
 #[derive(Component, Default, Clone)]
@@ -70,9 +129,11 @@ pub struct ActorAudio {
     pub land: Handle<AudioSource>,
     pub hit: Handle<AudioSource>,
     pub death: Handle<AudioSource>,
-    pub pickup: Handle<AudioSource>,
     pub unlocked: Handle<AudioSource>,
     pub victory: Handle<AudioSource>,
+    pub wall_slide: Handle<AudioSource>,
+    pub wall_jump: Handle<AudioSource>,
+    pub attack: Handle<AudioSource>,
 }
 
 #[derive(Debug, Clone)]
@@ -101,21 +162,42 @@ pub struct StartCarryEvent {
     pub carrier: Entity,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CarryRelease {
+    /// Let go of in place
+    Drop,
+    /// Sent flying at this velocity
+    Throw(Vec2),
+}
+
 #[derive(Clone, Event)]
 pub struct EndCarryEvent {
     pub picked_up: Entity,
     pub carrier: Entity,
+    pub release: CarryRelease,
 }
 
-#[derive(Clone, Event)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum ActorEvent {
     Launched,
     Landed,
-    _Hit,
+    Hit,
     Died,
     Win,
     Pickup,
-    _Unlock,
+    Unlock,
+    Checkpoint,
+    WallSlide,
+    WallJump,
+    Attack,
+}
+
+/// An `ActorEvent` from one tick. Sent as an event rather than kept on `ActorStatus`,
+/// so the presentation gets every tick's events once, however many ticks ran in a frame.
+#[derive(Clone, Debug, Event)]
+pub struct ActorTickEvent {
+    pub actor: Entity,
+    pub event: ActorEvent,
 }
 
 impl Squashy {
@@ -155,22 +237,35 @@ impl Plugin for ActorPlugin {
         app
         .add_event::<StartCarryEvent>()
         .add_event::<EndCarryEvent>()
+        .add_event::<ActorTickEvent>()
+        .add_systems(FixedUpdate, (
+            actor_status,
+            actor_movement,
+            actor_carry_startstop,
+            actor_carry,
+        ).chain().in_set(SimulationSet).run_if(in_state(GameState::Playing)))
+        .add_systems(FixedUpdate, actor_pickup_effects
+            .after(check_for_pickups)
+            .in_set(SimulationSet)
+            .run_if(in_state(GameState::Playing))
+        )
+        ;
+    }
+}
+
+/// Animation, squash and stretch, and sound for actors
+pub struct ActorPresentationPlugin;
+
+impl Plugin for ActorPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app
         .add_systems(Update,(
-            actor_status.before(actor_movement),
-            actor_movement.before(actor_event_clear),
-            actor_animations.before(actor_event_clear),
-            actor_event_clear,
-            squash_states.before(actor_event_clear),
+            actor_animations,
+            actor_squash_events,
+            squash_states,
             squash_animation,
-            actor_carry.after(actor_carry_startstop)
-        ))
-        .add_systems(Update,
-            (actor_squash_events, actor_pickup_effects, actor_carry_startstop, actor_audio)
-                .before(actor_event_clear)
-                .after(actor_status)
-                .after(actor_movement)
-                .after(check_for_pickups)
-        )
+            actor_audio,
+        ).run_if(in_state(GameState::Playing)))
         ;
     }
 }
@@ -186,16 +281,27 @@ impl Default for Actor {
             down_gravity: 500.,
             jump_speed: 800.,
             jump_time: 0.2,
+            jump_cut: 0.5,
+            coyote_time: 0.1,
+            jump_buffer_time: 0.1,
+            wall_slide_speed: 60.,
+            wall_jump_speed: 160.,
+            wall_jump_kick: 120.,
+            wall_jump_lock_time: 0.15,
+            hitstun_time: 0.25,
             move_input: 0.,
+            aim_input: Vec2::ZERO,
             jump_input: false,
             grab_input: false,
-            can_jump: false,
+            yell_input: false,
+            attack_input: false,
         }
     }
 }
 
 pub fn actor_status(
-    time: Res<Time>,
+    fixed_time: Res<FixedTime>,
+    mut event_writer: EventWriter<ActorTickEvent>,
     mut actor_query: Query<(
         Entity,
         &Transform,
@@ -206,6 +312,8 @@ pub fn actor_status(
     )>,
     rapier_context: Res<RapierContext>,
 ) {
+    let dt = fixed_time.period.as_secs_f32();
+
     for (entity, transform, mut actor_status, controller_output, carrier_opt, carried_opt) in &mut actor_query {
         let distance = 1.0;
         let shape = Collider::capsule_y(5.5, 2.5);
@@ -223,7 +331,10 @@ pub fn actor_status(
         }
         
         if !actor_status.grounded && cast_grounded {
-            actor_status.event = Some(ActorEvent::Landed);
+            event_writer.send(ActorTickEvent {
+                actor: entity,
+                event: ActorEvent::Landed,
+            });
         }
 
         actor_status.grounded = cast_grounded;
@@ -233,24 +344,18 @@ pub fn actor_status(
             actor_status.air_timer = 0.;
             actor_status.velocity.y = 0.;
         } else {
-            actor_status.air_timer += time.delta_seconds();
+            actor_status.air_timer += dt;
         }
 
-        if let Some((_, _)) =
-            rapier_context.cast_shape(shape_pos, 0., Vec2::new(distance, 0.), &shape, 1., filter)
-        {
-            actor_status.right_wall = true;
-        } else {
-            actor_status.right_wall = false;
-        }
+        let right_hit =
+            rapier_context.cast_shape(shape_pos, 0., Vec2::new(distance, 0.), &shape, 1., filter);
+        actor_status.right_wall = right_hit.is_some();
+        actor_status.right_wall_entity = right_hit.map(|(entity, _)| entity);
 
-        if let Some((_, _)) =
-            rapier_context.cast_shape(shape_pos, 0., Vec2::new(-distance, 0.), &shape, 1., filter)
-        {
-            actor_status.left_wall = true;
-        } else {
-            actor_status.left_wall = false;
-        }
+        let left_hit =
+            rapier_context.cast_shape(shape_pos, 0., Vec2::new(-distance, 0.), &shape, 1., filter);
+        actor_status.left_wall = left_hit.is_some();
+        actor_status.left_wall_entity = left_hit.map(|(entity, _)| entity);
         
         actor_status.carried = carried_opt.is_some();
         
@@ -265,45 +370,114 @@ pub fn actor_status(
 
 pub fn actor_pickup_effects(
     mut soul_pickup_events: EventReader<PickupEvent>,
-    mut actor_statuses: Query<&mut ActorStatus, With<PickupCollector>>,
+    mut event_writer: EventWriter<ActorTickEvent>,
+    collector_query: Query<(), (With<ActorStatus>, With<PickupCollector>)>,
 ) {
     for ev in soul_pickup_events.iter() {
-        if let Ok(mut status) = actor_statuses.get_mut(ev.collector_entity) {
-            status.event = Some(ActorEvent::Pickup);
+        if collector_query.contains(ev.collector_entity) {
+            event_writer.send(ActorTickEvent {
+                actor: ev.collector_entity,
+                event: ActorEvent::Pickup,
+            });
         }
     }
 }
 
+/// Keeps everything in a carry stack on top of whoever is standing at the bottom of it,
+/// each carrier's offset adding to the ones below
 pub fn actor_carry(
-    mut carrier_query: Query<(&Transform, &Carrier), Without<Carried>>,
-    mut carryable_query: Query<(&mut Transform, &Carried)>,
+    carrier_query: Query<&Carrier>,
+    carried_query: Query<(Entity, &Carried)>,
+    mut transform_query: Query<&mut Transform>,
 ) {
-    for (carrier_transform, carrier) in &mut carrier_query {
-        if let Some(carried_entity) = carrier.carrying {
-            if let Ok((mut carryable_transform, _carried)) = carryable_query.get_mut(carried_entity) {
-                carryable_transform.translation = carrier_transform.translation + carrier.carry_offset;
+    let mut placements = Vec::new();
+
+    for (carried_entity, _) in &carried_query {
+        let mut offset = Vec3::ZERO;
+        let mut bottom = carried_entity;
+
+        for _ in 0..MAX_CARRY_STACK {
+            let Ok((_, carried)) = carried_query.get(bottom) else {
+                break;
+            };
+
+            // Let go of this tick, stays where it is
+            match carrier_query.get(carried.held_by) {
+                Ok(carrier) if carrier.carrying == Some(bottom) => offset += carrier.carry_offset,
+                _ => break,
             }
+
+            bottom = carried.held_by;
+        }
+
+        if bottom != carried_entity && !carried_query.contains(bottom) {
+            placements.push((carried_entity, bottom, offset));
         }
     }
+
+    for (carried_entity, bottom, offset) in placements {
+        let Ok(bottom_transform) = transform_query.get(bottom) else {
+            continue;
+        };
+        let translation = bottom_transform.translation + offset;
+
+        if let Ok(mut carried_transform) = transform_query.get_mut(carried_entity) {
+            carried_transform.translation = translation;
+        }
+    }
+}
+
+/// Whether `entity` sits anywhere above `holder` in a carry stack
+fn is_carried_by(entity: Entity, holder: Entity, held_by: impl Fn(Entity) -> Option<Entity>) -> bool {
+    let mut current = entity;
+
+    for _ in 0..MAX_CARRY_STACK {
+        match held_by(current) {
+            Some(below) if below == holder => return true,
+            Some(below) => current = below,
+            None => return false,
+        }
+    }
+
+    true
 }
 
 pub fn actor_carry_startstop(
-    mut carrier_query: Query<(Entity, &Actor, &GlobalTransform, &mut Carrier), Without<Carried>>,
-    mut carryable_query: Query<(&Carryable, Option<&Actor>, Option<&mut ActorStatus>, Option<&mut Carried>)>,
+    mut carrier_query: Query<(Entity, &Actor, &GlobalTransform, &mut Carrier)>,
+    carryable_query: Query<(Entity, &Carryable, Option<&Actor>, Option<&Carried>)>,
+    mut status_query: Query<&mut ActorStatus>,
     rapier_context: Res<RapierContext>,
     mut start_carry_event_writer: EventWriter<StartCarryEvent>,
     mut end_carry_event_writer: EventWriter<EndCarryEvent>,
+    mut event_writer: EventWriter<ActorTickEvent>,
     mut commands: Commands,
 ) {
+    // Pick ups this tick, whose `Carried` isn't inserted until the commands run
+    let mut picked_up: HashMap<Entity, Entity> = HashMap::new();
+
     for (carrier_entity, actor, carrier_transform, mut carrier) in &mut carrier_query {
         if actor.grab_input {
             //println!("grabby");
             
             if let Some(carried_entity) = carrier.carrying {
-                // Already carrying, drop it
+                // Already carrying, throw it if aiming or on the move, otherwise drop it
+                let carrier_velocity = status_query
+                    .get(carrier_entity)
+                    .map(|status| (status.velocity, status.facing_left))
+                    .unwrap_or_default();
+                let release = throw_release(actor, &carrier, carrier_velocity.0, carrier_velocity.1);
+
+                if let CarryRelease::Throw(velocity) = release {
+                    if let Ok(mut status) = status_query.get_mut(carried_entity) {
+                        status.launch = Some(velocity);
+                        status.thrown = true;
+                    }
+                }
+
                 end_carry_event_writer.send(EndCarryEvent {
                     carrier: carrier_entity,
                     picked_up: carried_entity,
+                    release,
                 });
                 
                 carrier.carrying = None;
@@ -320,8 +494,21 @@ pub fn actor_carry_startstop(
                     if entity == carrier_entity {
                         return true;
                     }
-                    else if let Ok((_, _, _, carried)) = carryable_query.get_mut(entity) {
-                        if carried.is_none() {
+                    else if let Ok((_, _, _, carried)) = carryable_query.get(entity) {
+                        let held_by = |entity| {
+                            picked_up.get(&entity).copied().or_else(|| {
+                                carryable_query
+                                    .get(entity)
+                                    .ok()
+                                    .and_then(|(_, _, _, carried)| carried.map(|carried| carried.held_by))
+                            })
+                        };
+
+                        // Picking up the stack the carrier is standing on would make a loop
+                        if carried.is_none()
+                            && !picked_up.contains_key(&entity)
+                            && !is_carried_by(carrier_entity, entity, held_by)
+                        {
                             start_carry_event_writer.send(StartCarryEvent{
                                 carrier: carrier_entity,
                                 picked_up: entity,
@@ -330,6 +517,7 @@ pub fn actor_carry_startstop(
                             //println!("Grab");
                             
                             carrier.carrying = Some(entity);
+                            picked_up.insert(entity, carrier_entity);
                             commands.entity(entity).insert(Carried{ held_by: carrier_entity });
                             
                             return false;
@@ -341,20 +529,28 @@ pub fn actor_carry_startstop(
         }
     }
     
-    for (_carryable, opt_actor, opt_actor_status, opt_carried) in &mut carryable_query {
+    // Jumping off only splits the stack below, anything on top comes along
+    for (carried_entity, _carryable, opt_actor, opt_carried) in &carryable_query {
         if let Some(carried) = opt_carried {
             if let Some(actor) = opt_actor {
                 if actor.jump_input {
-                    if let Ok((_, _, _, mut carrier)) = carrier_query.get_mut(carried.held_by) {
-                        commands.entity(carrier.carrying.unwrap()).remove::<Carried>();
-                        carrier.carrying = None;
+                    let Ok((_, _, _, mut carrier)) = carrier_query.get_mut(carried.held_by) else {
+                        continue;
+                    };
+                    if carrier.carrying != Some(carried_entity) {
+                        continue;
                     }
-                    
-                    if let Some(mut status) = opt_actor_status {
-                        status.velocity.y = actor.jump_speed;
-                        if status.grounded {
-                            status.event = Some(ActorEvent::Launched);
-                        }
+
+                    commands.entity(carried_entity).remove::<Carried>();
+                    carrier.carrying = None;
+
+                    // Launched rather than jumping, the held jump can't start a jump of its own
+                    if let Ok(mut status) = status_query.get_mut(carried_entity) {
+                        status.launch = Some(Vec2::new(status.velocity.x, actor.jump_speed));
+                        event_writer.send(ActorTickEvent {
+                            actor: carried_entity,
+                            event: ActorEvent::Launched,
+                        });
                     }
                 }
             }
@@ -362,11 +558,40 @@ pub fn actor_carry_startstop(
     }
 }
 
+/// Throws forward when moving or aiming sideways, straight up when aiming up, and otherwise
+/// just lets go. Throws carry on with the carrier's own speed.
+fn throw_release(actor: &Actor, carrier: &Carrier, velocity: Vec2, facing_left: bool) -> CarryRelease {
+    let aim = actor.aim_input;
+    let moving = velocity.x.abs() > 20.;
+
+    if aim.x.abs() > 0.1 || (moving && aim.y > -0.5) {
+        let forward = if aim.x.abs() > 0.1 {
+            aim.x.signum()
+        } else if facing_left {
+            -1.
+        } else {
+            1.
+        };
+
+        CarryRelease::Throw(Vec2::new(
+            forward * carrier.throw_speed.x + velocity.x,
+            carrier.throw_speed.y,
+        ))
+    } else if aim.y > 0.5 {
+        CarryRelease::Throw(Vec2::new(velocity.x, carrier.throw_speed.x.max(carrier.throw_speed.y)))
+    } else {
+        CarryRelease::Drop
+    }
+}
+
 pub fn actor_movement(
-    time: Res<Time>,
-    mut actor_query: Query<(&Actor, &mut ActorStatus, &mut KinematicCharacterController, Option<&Carried>)>,
+    fixed_time: Res<FixedTime>,
+    mut event_writer: EventWriter<ActorTickEvent>,
+    mut actor_query: Query<(Entity, &Actor, &mut ActorStatus, &mut KinematicCharacterController, Option<&Carried>)>,
 ) {
-    for (actor, mut status, mut controller, opt_carry) in &mut actor_query {
+    let dt = fixed_time.period.as_secs_f32();
+
+    for (entity, actor, mut status, mut controller, opt_carry) in &mut actor_query {
         // Track facing based on input seperately
         if actor.move_input > 0.1 {
             status.facing_left = false;
@@ -384,42 +609,146 @@ pub fn actor_movement(
             
         }
         else {
-            let dir_match = actor.move_input.signum() == status.velocity.x.signum();
-            let accel = if dir_match { actor.accel } else { actor.deccel };
-            status.velocity.x += actor.move_input * accel * time.delta_seconds();
-            
-            if actor.move_input.abs() < 0.1 {
-                status.velocity.x *= 1.0 - actor.drag;
+            if status.grounded || status.left_wall || status.right_wall {
+                status.thrown = false;
+            }
+
+            if let Some(launch) = status.launch.take() {
+                status.velocity = launch;
+                status.jump_state = JumpState::Falling { jumped: true };
+                status.jump_buffer = None;
+            }
+
+            if let Some(knockback) = status.knockback.take() {
+                // A hit ends any jump in progress and takes control away for a moment
+                status.velocity = knockback;
+                status.hitstun = actor.hitstun_time;
+                status.jump_state = JumpState::Falling { jumped: true };
+                status.jump_buffer = None;
+            }
+
+            // Sliding needs the actor to be falling while pushing into a wall
+            let pushing_wall = (actor.move_input > 0.1 && status.right_wall)
+                || (actor.move_input < -0.1 && status.left_wall);
+            let wall_sliding = !status.grounded && pushing_wall && status.velocity.y <= 0.;
+            if wall_sliding && !status.wall_sliding {
+                event_writer.send(ActorTickEvent {
+                    actor: entity,
+                    event: ActorEvent::WallSlide,
+                });
+            }
+            status.wall_sliding = wall_sliding;
+
+            if status.hitstun > 0. {
+                status.hitstun -= dt;
+            } else if status.wall_jump_lock > 0. {
+                // Hold the kick away from the wall until control comes back
+                status.wall_jump_lock -= dt;
+            } else if status.thrown {
+                // Flies on the throw alone, no steering or drag
+            } else {
+                let dir_match = actor.move_input.signum() == status.velocity.x.signum();
+                let accel = if dir_match { actor.accel } else { actor.deccel };
+                status.velocity.x += actor.move_input * accel * dt;
+                
+                if actor.move_input.abs() < 0.1 {
+                    // Drag is the share of speed lost every 60th of a second
+                    status.velocity.x *= (1.0 - actor.drag).powf(dt * 60.);
+                }
+        
+                status.velocity.x = status.velocity.x.clamp(-actor.move_speed, actor.move_speed);
             }
     
-            status.velocity.x = status.velocity.x.clamp(-actor.move_speed, actor.move_speed);
-    
-            if (status.velocity.x > 0. && status.right_wall)
+            if let Some(push_speed) = status.pushing {
+                status.velocity.x = status.velocity.x.clamp(-push_speed, push_speed);
+            } else if (status.velocity.x > 0. && status.right_wall)
                 || (status.velocity.x < 0. && status.left_wall)
             {
                 status.velocity.x = 0.;
             }
-    
-            if actor.can_jump && actor.jump_input {
-                status.velocity.y = actor.jump_speed;
-    
-                if status.grounded {
-                    status.event = Some(ActorEvent::Launched);
+
+            if status.hitstun <= 0. {
+                if let Some(event) = update_jump(actor, &mut status, dt) {
+                    event_writer.send(ActorTickEvent { actor: entity, event });
                 }
-            } else if !status.grounded {
+            }
+
+            if !status.grounded && !matches!(status.jump_state, JumpState::Ascending(_)) {
                 status.velocity.y -= if status.velocity.y > 0. {
                     actor.down_gravity
                 } else {
                     actor.up_gravity
-                } * time.delta_seconds();
+                } * dt;
+
+                if status.wall_sliding {
+                    status.velocity.y = status.velocity.y.max(-actor.wall_slide_speed);
+                }
             }
     
-            controller.translation = Some(time.delta_seconds() * status.velocity);
+            controller.translation = Some(dt * status.velocity);
         }
-        status.last_dt = time.delta_seconds();
+        status.jump_held = actor.jump_input;
+        status.last_dt = dt;
     }
 }
 
+/// Launches at most once per press, from a buffered press on landing or within coyote time,
+/// then keeps rising while jump is held up to `jump_time`
+fn update_jump(actor: &Actor, status: &mut ActorStatus, dt: f32) -> Option<ActorEvent> {
+    if actor.jump_input && !status.jump_held {
+        status.jump_buffer = Some(0.);
+    } else if let Some(age) = status.jump_buffer {
+        status.jump_buffer = Some(age + dt).filter(|age| *age <= actor.jump_buffer_time);
+    }
+
+    status.jump_state = match status.jump_state {
+        JumpState::Grounded if !status.grounded => JumpState::Falling { jumped: false },
+        JumpState::Falling { .. } if status.grounded => JumpState::Grounded,
+        state => state,
+    };
+
+    let touching_wall = status.left_wall || status.right_wall;
+    let can_launch = match status.jump_state {
+        JumpState::Grounded => true,
+        JumpState::Falling { jumped } => !jumped && status.air_timer <= actor.coyote_time,
+        JumpState::Ascending(_) => false,
+    };
+
+    let mut event = None;
+    if status.jump_buffer.is_some() && can_launch {
+        status.jump_buffer = None;
+        status.velocity.y = actor.jump_speed;
+        status.jump_state = JumpState::Ascending(0.);
+        event = Some(ActorEvent::Launched);
+    } else if status.jump_buffer.is_some() && !status.grounded && touching_wall {
+        let away = if status.right_wall { -1. } else { 1. };
+        status.jump_buffer = None;
+        status.velocity.x = away * actor.wall_jump_kick;
+        status.velocity.y = actor.wall_jump_speed;
+        status.wall_jump_lock = actor.wall_jump_lock_time;
+        status.wall_sliding = false;
+        status.jump_state = JumpState::Falling { jumped: true };
+        return Some(ActorEvent::WallJump);
+    }
+
+    if let JumpState::Ascending(rising_time) = status.jump_state {
+        // A ceiling stops the rise early, the first frame is skipped as it is still on the ground
+        let bonked = rising_time > 0. && !status.grounded && status.velocity.y <= 0.;
+
+        if !actor.jump_input {
+            status.velocity.y *= actor.jump_cut;
+            status.jump_state = JumpState::Falling { jumped: true };
+        } else if rising_time >= actor.jump_time || bonked {
+            status.jump_state = JumpState::Falling { jumped: true };
+        } else {
+            status.velocity.y = actor.jump_speed;
+            status.jump_state = JumpState::Ascending(rising_time + dt);
+        }
+    }
+
+    event
+}
+
 fn actor_animations(
     mut actor_query: Query<(
         &ActorStatus,
@@ -439,12 +768,22 @@ fn actor_animations(
         else if status.carried {
             animator.set_row(anim_states.idle_row);
         }
+        else if status.yelling > 0. {
+            animator.set_row(anim_states.yell_row);
+        }
+        else if status.pushing.is_some() {
+            animator.set_row(anim_states.push_row);
+        }
         else if status.grounded {
             if status.velocity.x.abs() > 20. {
                 animator.set_row(anim_states.run_row);
             } else {
                 animator.set_row(anim_states.idle_row);
             }
+        } else if status.wall_sliding {
+            animator.set_row(anim_states.wall_slide_row);
+        } else if status.wall_jump_lock > 0. {
+            animator.set_row(anim_states.wall_jump_row);
         } else {
             if status.velocity.y > -10. {
                 animator.set_row(anim_states.jump_row);
@@ -457,35 +796,50 @@ fn actor_animations(
     }
 }
 
-fn actor_audio(
-    actor_query: Query<(&ActorStatus, &ActorAudio)>, 
+pub fn actor_audio(
+    mut actor_events: EventReader<ActorTickEvent>,
+    actor_query: Query<&ActorAudio>,
+    settings: Res<Settings>,
     mut commands: Commands
 ) {
-    for (status, actor_sounds) in &actor_query {
-        if let Some(event) = &status.event {
+    for ev in actor_events.iter() {
+        if let Ok(actor_sounds) = actor_query.get(ev.actor) {
+            let source = match ev.event {
+                ActorEvent::Launched => actor_sounds.jump.clone(),
+                ActorEvent::Landed => actor_sounds.land.clone(),
+                ActorEvent::Hit => actor_sounds.hit.clone(),
+                ActorEvent::Died => actor_sounds.death.clone(),
+                // Each pickup plays its own sound, see play_pickup_sounds
+                ActorEvent::Pickup => continue,
+                ActorEvent::Unlock | ActorEvent::Checkpoint => actor_sounds.unlocked.clone(),
+                ActorEvent::Win => actor_sounds.victory.clone(),
+                ActorEvent::WallSlide => actor_sounds.wall_slide.clone(),
+                ActorEvent::WallJump => actor_sounds.wall_jump.clone(),
+                ActorEvent::Attack => actor_sounds.attack.clone(),
+            };
+
             commands.spawn(AudioSourceBundle {
-                settings: PlaybackSettings::DESPAWN,
-                source: match event {
-                    ActorEvent::Launched => actor_sounds.jump.clone(),
-                    ActorEvent::Landed => actor_sounds.land.clone(),
-                    ActorEvent::_Hit => actor_sounds.hit.clone(),
-                    ActorEvent::Died => actor_sounds.death.clone(),
-                    ActorEvent::Pickup => actor_sounds.pickup.clone(),
-                    ActorEvent::_Unlock => actor_sounds.unlocked.clone(),
-                    ActorEvent::Win => actor_sounds.victory.clone(),
-                }
+                settings: settings.effect_playback(),
+                source,
             });
         }
     }
 }
 
 
-fn actor_squash_events(mut actor_query: Query<(&ActorStatus, &mut Squashy)>) {
-    for (status, mut squish) in actor_query.iter_mut() {
-        if let Some(event) = &status.event {
-            match event {
-                ActorEvent::Launched => squish.change_state(Some(SquashStretchState::Stretch)),
-                ActorEvent::Landed => squish.change_state(Some(SquashStretchState::Squash)),
+fn actor_squash_events(
+    mut actor_events: EventReader<ActorTickEvent>,
+    mut actor_query: Query<&mut Squashy>,
+) {
+    for ev in actor_events.iter() {
+        if let Ok(mut squish) = actor_query.get_mut(ev.actor) {
+            match ev.event {
+                ActorEvent::Launched | ActorEvent::WallJump | ActorEvent::Attack => {
+                    squish.change_state(Some(SquashStretchState::Stretch))
+                }
+                ActorEvent::Landed | ActorEvent::Hit => {
+                    squish.change_state(Some(SquashStretchState::Squash))
+                }
                 _ => (),
             };
         }
@@ -513,7 +867,7 @@ fn squash_states(time: Res<Time>, mut squish_query: Query<&mut Squashy>) {
     }
 }
 
-fn squash_animation(mut squish_query: Query<(&Squashy, &mut TextureAtlasSprite)>) {
+pub fn squash_animation(mut squish_query: Query<(&Squashy, &mut TextureAtlasSprite)>) {
     for (squish, mut sprite) in squish_query.iter_mut() {
         if squish.state.is_some() {
             let t = squish.state_time / squish.get_current_state_max_time();
@@ -532,8 +886,3 @@ fn squash_animation(mut squish_query: Query<(&Squashy, &mut TextureAtlasSprite)>
     }
 }
 
-pub fn actor_event_clear(mut actor_query: Query<&mut ActorStatus>) {
-    for mut status in &mut actor_query {
-        status.event = None;
-    }
-}
@@ -1,3 +1,4 @@
+use crate::fixed_step::interpolate_transforms;
 use crate::player::Player;
 use bevy::prelude::*;
 use bevy_ecs_ldtk::LdtkLevel;
@@ -16,7 +17,11 @@ impl Plugin for CameraPlugin {
                 aspect_ratio: 16. / 9.,
             })
             .add_systems(Startup, spawn_camera)
-            .add_systems(Update, (camera_fit_inside_current_level, update_aspect_ratio))
+            .add_systems(Update, (
+                interpolate_transforms,
+                camera_fit_inside_current_level.after(interpolate_transforms),
+                update_aspect_ratio,
+            ))
             ;
     }
 }
@@ -0,0 +1,146 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    actor::{ActorEvent, ActorTickEvent},
+    fixed_step::SimulationSet,
+    player::{Dying, Player},
+    world::Labeled,
+    GameState,
+};
+
+pub struct CheckpointPlugin;
+
+#[derive(Component, Default, Clone, Debug)]
+pub struct Checkpoint {
+    pub active: bool,
+}
+
+/// Where players come back after dying, shared by everyone in the current level.
+/// Cleared whenever a level spawns, players then go back to where they started.
+#[derive(Resource, Default, Debug)]
+pub struct ActiveCheckpoint {
+    pub position: Option<Vec3>,
+}
+
+#[derive(Clone, Default, Bundle)]
+pub struct CheckpointBundle {
+    pub sprite_sheet_bundle: SpriteSheetBundle,
+    pub collider: Collider,
+    pub sensor: Sensor,
+    pub label: Labeled,
+    pub checkpoint: Checkpoint,
+}
+
+impl LdtkEntity for CheckpointBundle {
+    fn bundle_entity(
+        _entity_instance: &EntityInstance,
+        _layer_instance: &LayerInstance,
+        _tileset: Option<&Handle<Image>>,
+        _tileset_definition: Option<&TilesetDefinition>,
+        asset_server: &AssetServer,
+        texture_atlases: &mut Assets<TextureAtlas>,
+    ) -> Self {
+        let texture_handle = asset_server.load("sprites/checkpoint.png");
+        let texture_atlas =
+            TextureAtlas::from_grid(texture_handle, Vec2::new(16., 32.), 2, 1, None, None);
+
+        CheckpointBundle {
+            sprite_sheet_bundle: SpriteSheetBundle {
+                transform: Transform::from_translation(Vec3::new(0., 0., 0.5)),
+                texture_atlas: texture_atlases.add(texture_atlas),
+                ..Default::default()
+            },
+            collider: Collider::cuboid(8., 16.),
+            sensor: Sensor,
+            label: Labeled {
+                name: String::from("checkpoint"),
+            },
+            checkpoint: Checkpoint::default(),
+        }
+    }
+}
+
+impl Plugin for CheckpointPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveCheckpoint>()
+            .add_systems(Update, reset_checkpoint)
+            .add_systems(FixedUpdate, activate_checkpoints
+                .in_set(SimulationSet)
+                .run_if(in_state(GameState::Playing))
+            )
+            ;
+    }
+}
+
+pub struct CheckpointPresentationPlugin;
+
+impl Plugin for CheckpointPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, checkpoint_sprites.run_if(in_state(GameState::Playing)));
+    }
+}
+
+fn reset_checkpoint(
+    mut level_events: EventReader<LevelEvent>,
+    mut active_checkpoint: ResMut<ActiveCheckpoint>,
+) {
+    for ev in level_events.iter() {
+        if let LevelEvent::Spawned(_) = ev {
+            active_checkpoint.position = None;
+        }
+    }
+}
+
+/// The last checkpoint touched by any living player becomes the active one
+fn activate_checkpoints(
+    rapier_context: Res<RapierContext>,
+    mut active_checkpoint: ResMut<ActiveCheckpoint>,
+    mut event_writer: EventWriter<ActorTickEvent>,
+    player_query: Query<(Entity, &Transform), (With<Player>, Without<Dying>)>,
+    mut checkpoint_query: Query<(Entity, &Transform, &mut Checkpoint), Without<Player>>,
+) {
+    for (player, transform) in &player_query {
+        let shape = Collider::capsule_y(5.5, 3.5);
+        let filter = QueryFilter::new();
+        let shape_pos = transform.translation.truncate();
+
+        let mut touched = None;
+        rapier_context.intersections_with_shape(shape_pos, 0., &shape, filter, |entity| -> bool {
+            if checkpoint_query.contains(entity) {
+                touched = Some(entity);
+                return false;
+            }
+            true
+        });
+
+        let Some(touched) = touched else {
+            continue;
+        };
+
+        for (entity, checkpoint_transform, mut checkpoint) in &mut checkpoint_query {
+            let active = entity == touched;
+
+            if active && !checkpoint.active {
+                active_checkpoint.position = Some(checkpoint_transform.translation);
+                event_writer.send(ActorTickEvent {
+                    actor: player,
+                    event: ActorEvent::Checkpoint,
+                });
+            }
+
+            if checkpoint.active != active {
+                checkpoint.active = active;
+            }
+        }
+    }
+}
+
+fn checkpoint_sprites(
+    mut checkpoint_query: Query<(&Checkpoint, &mut TextureAtlasSprite), Changed<Checkpoint>>,
+) {
+    for (checkpoint, mut sprite) in &mut checkpoint_query {
+        sprite.index = checkpoint.active as usize;
+    }
+}
@@ -0,0 +1,182 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    fixed_step::SimulationSet,
+    health::{DamageEvent, Damageable},
+    loading::SpriteAssets,
+    melee::actor_attack,
+    pickup::{PickupBundle, PickupType},
+    world::Labeled,
+    GameState,
+};
+
+pub struct CuttablePlugin;
+
+/// Scenery that is cut down by any hit, then grows back
+#[derive(Component, Clone, Debug)]
+pub struct Cuttable {
+    pub regrow_time: f32,
+    /// Time left before it has grown back, zero while standing
+    pub regrowing: f32,
+    /// Left behind each time it is cut
+    pub drop: Option<PickupType>,
+}
+
+impl Default for Cuttable {
+    fn default() -> Self {
+        Cuttable {
+            regrow_time: 8.,
+            regrowing: 0.,
+            drop: Some(PickupType::Coin),
+        }
+    }
+}
+
+impl Cuttable {
+    pub fn is_cut(&self) -> bool {
+        self.regrowing > 0.
+    }
+}
+
+#[derive(Clone, Default, Bundle)]
+pub struct WheatBundle {
+    pub sprite_bundle: SpriteBundle,
+    pub collider: Collider,
+    pub label: Labeled,
+    pub sensor: Sensor,
+    pub damageable: Damageable,
+    pub cuttable: Cuttable,
+}
+
+impl WheatBundle {
+    pub fn new(cuttable: Cuttable, asset_server: &AssetServer) -> Self {
+        WheatBundle {
+            sprite_bundle: SpriteBundle {
+                texture: asset_server.load("sprites/wheat_grown.png"),
+                transform: Transform::from_translation(Vec3::new(0., 0., 0.5)),
+                ..default()
+            },
+            collider: Collider::cuboid(8., 8.),
+            label: Labeled {
+                name: String::from("wheat"),
+            },
+            sensor: Sensor,
+            damageable: Damageable,
+            cuttable,
+        }
+    }
+}
+
+impl LdtkEntity for WheatBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        _layer_instance: &LayerInstance,
+        _tileset: Option<&Handle<Image>>,
+        _tileset_definition: Option<&TilesetDefinition>,
+        asset_server: &AssetServer,
+        _texture_atlases: &mut Assets<TextureAtlas>,
+    ) -> Self {
+        let mut cuttable = Cuttable::default();
+
+        for field in entity_instance.field_instances.iter() {
+            match field.identifier.as_str() {
+                "RegrowTime" => {
+                    if let FieldValue::Float(Some(value)) = field.value {
+                        cuttable.regrow_time = value;
+                    }
+                }
+                "Drop" => {
+                    if let FieldValue::String(value) = &field.value {
+                        cuttable.drop = value.as_deref().and_then(PickupType::from_identifier);
+                    }
+                }
+                unknown => println!("Unknown field \"{}\" on LDtk wheat object!", unknown),
+            }
+        }
+
+        WheatBundle::new(cuttable, asset_server)
+    }
+}
+
+impl Plugin for CuttablePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, (
+                regrow_cuttables,
+                cut_cuttables.after(actor_attack),
+            ).in_set(SimulationSet).run_if(in_state(GameState::Playing)))
+            ;
+    }
+}
+
+pub struct CuttablePresentationPlugin;
+
+impl Plugin for CuttablePresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, wheat_sprites.run_if(in_state(GameState::Playing)));
+    }
+}
+
+fn regrow_cuttables(fixed_time: Res<FixedTime>, mut cuttable_query: Query<&mut Cuttable>) {
+    let dt = fixed_time.period.as_secs_f32();
+
+    for mut cuttable in &mut cuttable_query {
+        if cuttable.is_cut() {
+            cuttable.regrowing = (cuttable.regrowing - dt).max(0.);
+        }
+    }
+}
+
+/// Cuts down anything standing that was hit, dropping its pickup in the same level
+fn cut_cuttables(
+    mut commands: Commands,
+    mut damage_events: EventReader<DamageEvent>,
+    mut cuttable_query: Query<(&mut Cuttable, &Transform, Option<&Parent>)>,
+    asset_server: Res<AssetServer>,
+) {
+    for ev in damage_events.iter() {
+        let Ok((mut cuttable, transform, parent)) = cuttable_query.get_mut(ev.target) else {
+            continue;
+        };
+
+        if cuttable.is_cut() {
+            continue;
+        }
+
+        cuttable.regrowing = cuttable.regrow_time;
+
+        if let Some(pickup_type) = cuttable.drop.clone() {
+            let mut bundle = PickupBundle::new(
+                pickup_type.clone(),
+                pickup_type.default_sprite_path(),
+                pickup_type.default_sound_path(),
+                &asset_server,
+            );
+            bundle.sprite_bundle.transform.translation =
+                transform.translation.truncate().extend(bundle.sprite_bundle.transform.translation.z);
+
+            let pickup = commands.spawn(bundle).id();
+            if let Some(parent) = parent {
+                commands.entity(parent.get()).add_child(pickup);
+            }
+        }
+    }
+}
+
+fn wheat_sprites(
+    mut cuttable_query: Query<(&Cuttable, &mut Handle<Image>), Changed<Cuttable>>,
+    sprites: Res<SpriteAssets>,
+) {
+    for (cuttable, mut texture) in &mut cuttable_query {
+        let next = if cuttable.is_cut() {
+            &sprites.texture_wheat_chopped
+        } else {
+            &sprites.texture_wheat_grown
+        };
+
+        if *texture != *next {
+            *texture = next.clone();
+        }
+    }
+}
@@ -2,12 +2,63 @@ use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use crate::{sprite_anim::SpriteAnimator, world::Labeled};
+use crate::{
+    actor::{actor_pickup_effects, ActorEvent, ActorStatus, ActorTickEvent},
+    fixed_step::SimulationSet,
+    pickup::{PickupEvent, PickupType},
+    sprite_anim::SpriteAnimator,
+    world::Labeled,
+    GameState,
+};
+
+pub struct DoorPlugin;
+
+/// Souls collected in the currently loaded level, reset whenever a level spawns
+#[derive(Resource, Default, Debug)]
+pub struct LevelSouls {
+    pub collected: usize,
+}
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
 pub struct Door {
     pub next_level: usize,
     pub required_souls: usize,
+    /// Set once a player goes through, so the level is only completed once
+    pub entered: bool,
+}
+
+impl Door {
+    pub fn is_open(&self, level_souls: &LevelSouls) -> bool {
+        level_souls.collected >= self.required_souls
+    }
+}
+
+/// The door's open sprite sheet, swapped in once enough souls are collected
+#[derive(Clone, Default, Component)]
+pub struct OpenDoorAtlas(pub Handle<TextureAtlas>);
+
+impl Plugin for DoorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelSouls>()
+            .add_systems(Update, reset_level_souls)
+            .add_systems(FixedUpdate, collect_souls
+                .after(actor_pickup_effects)
+                .in_set(SimulationSet)
+                .run_if(in_state(GameState::Playing))
+            )
+            ;
+    }
+}
+
+pub struct DoorPresentationPlugin;
+
+impl Plugin for DoorPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, open_door_sprites
+            .run_if(in_state(GameState::Playing))
+            .run_if(resource_changed::<LevelSouls>())
+        );
+    }
 }
 
 #[derive(Clone, Default, Bundle)]
@@ -19,6 +70,7 @@ pub struct DoorBundle {
     pub sensor: Sensor,
     pub active_events: ActiveEvents,
     pub door: Door,
+    pub open_atlas: OpenDoorAtlas,
 }
 
 impl LdtkEntity for DoorBundle {
@@ -48,10 +100,22 @@ impl LdtkEntity for DoorBundle {
             }
         }
 
-        let texture_handle = asset_server.load("sprites/door_closed.png");
-        let texture_atlas =
-            TextureAtlas::from_grid(texture_handle, Vec2::new(16., 32.), 4, 1, None, None);
-        let texture_atlas_handle = texture_atlases.add(texture_atlas);
+        let open_atlas = texture_atlases.add(TextureAtlas::from_grid(
+            asset_server.load("sprites/door_open.png"),
+            Vec2::new(16., 32.),
+            4,
+            1,
+            None,
+            None,
+        ));
+        let texture_atlas_handle = if door.required_souls == 0 {
+            open_atlas.clone()
+        } else {
+            let texture_handle = asset_server.load("sprites/door_closed.png");
+            let texture_atlas =
+                TextureAtlas::from_grid(texture_handle, Vec2::new(16., 32.), 4, 1, None, None);
+            texture_atlases.add(texture_atlas)
+        };
 
         DoorBundle {
             sprite_sheet_bundle: SpriteSheetBundle {
@@ -67,6 +131,57 @@ impl LdtkEntity for DoorBundle {
             sensor: Sensor,
             active_events: ActiveEvents::COLLISION_EVENTS,
             door,
+            open_atlas: OpenDoorAtlas(open_atlas),
+        }
+    }
+}
+
+fn reset_level_souls(
+    mut level_events: EventReader<LevelEvent>,
+    mut level_souls: ResMut<LevelSouls>,
+) {
+    for ev in level_events.iter() {
+        if let LevelEvent::Spawned(_) = ev {
+            level_souls.collected = 0;
+        }
+    }
+}
+
+fn collect_souls(
+    mut pickup_events: EventReader<PickupEvent>,
+    mut level_souls: ResMut<LevelSouls>,
+    door_query: Query<&Door>,
+    mut event_writer: EventWriter<ActorTickEvent>,
+    actor_query: Query<(), With<ActorStatus>>,
+) {
+    for ev in pickup_events.iter() {
+        if ev.pickup_type != PickupType::Soul {
+            continue;
+        }
+
+        level_souls.collected += 1;
+
+        // Souls come in one at a time, so each door's threshold is met exactly once
+        let unlocked = door_query
+            .iter()
+            .any(|door| door.required_souls == level_souls.collected);
+
+        if unlocked && actor_query.contains(ev.collector_entity) {
+            event_writer.send(ActorTickEvent {
+                actor: ev.collector_entity,
+                event: ActorEvent::Unlock,
+            });
+        }
+    }
+}
+
+fn open_door_sprites(
+    level_souls: Res<LevelSouls>,
+    mut door_query: Query<(&Door, &OpenDoorAtlas, &mut Handle<TextureAtlas>)>,
+) {
+    for (door, open_atlas, mut atlas_handle) in &mut door_query {
+        if door.is_open(&level_souls) && *atlas_handle != open_atlas.0 {
+            *atlas_handle = open_atlas.0.clone();
         }
     }
 }
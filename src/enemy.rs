@@ -0,0 +1,698 @@
+use bevy::{prelude::*, sprite::Anchor};
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    actor::{
+        actor_movement, actor_status, Actor, ActorAnimationStates, ActorAudio, ActorStatus,
+        squash_animation, ActorEvent, ActorTickEvent, JumpState, SquashStretchState, Squashy,
+    },
+    fixed_step::{HitStop, RenderInterpolation, SimulationSet},
+    health::{apply_damage, invulnerability_flicker, DamageEvent, Damageable, Health},
+    player::{player_inputs, Dying, Player},
+    sprite_anim::SpriteAnimator,
+    world::Labeled,
+    GameState,
+};
+
+pub struct EnemyPlugin;
+
+/// How long a defeated enemy stays around fading out
+const DEFEAT_TIME: f32 = 0.5;
+
+/// How far in front of its feet an enemy looks for ground before walking on
+const LEDGE_LOOKAHEAD: f32 = 6.;
+
+/// How far above an enemy's center a falling player has to be for touching it to be a stomp
+const STOMP_HEIGHT: f32 = 8.;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum EnemyType {
+    #[default]
+    Sam,
+    Dampboi,
+}
+
+/// What an enemy does with its `Actor` inputs
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Behaviour {
+    /// Walks back and forth, turning at walls and ledges
+    #[default]
+    Patrol,
+    /// Patrols until a player comes in sight, then walks toward them
+    Chase,
+    /// Jumps every `hop_interval`, only moving while in the air
+    Hop,
+}
+
+#[derive(Component, Clone, Debug)]
+pub struct Enemy {
+    pub enemy_type: EnemyType,
+    pub behaviour: Behaviour,
+    /// Which way it is heading, -1 for left and 1 for right
+    pub direction: f32,
+    /// How close a player has to be to get chased
+    pub sight_range: f32,
+    pub hop_interval: f32,
+    /// Time left on the ground before the next hop
+    pub hop_timer: f32,
+    /// Landed on by a player, rather than beaten some other way
+    pub stomped: bool,
+}
+
+/// What happens when a player touches an enemy
+#[derive(Component, Clone, Debug)]
+pub struct ContactDamage {
+    /// Dealt to players touching the enemy from the side or below
+    pub damage: u32,
+    pub knockback: f32,
+    /// Whether landing on top defeats the enemy, otherwise that hurts like any other touch
+    pub stompable: bool,
+    /// Upward speed a player bounces off at after a stomp
+    pub bounce_speed: f32,
+    /// Ticks the simulation freezes for on a stomp
+    pub hit_stop_ticks: u32,
+}
+
+impl Default for ContactDamage {
+    fn default() -> Self {
+        ContactDamage {
+            damage: 1,
+            knockback: 150.,
+            stompable: true,
+            bounce_speed: 200.,
+            hit_stop_ticks: 4,
+        }
+    }
+}
+
+impl Default for Enemy {
+    fn default() -> Self {
+        Enemy::new(EnemyType::default(), EnemyType::default().default_behaviour())
+    }
+}
+
+impl Enemy {
+    pub fn new(enemy_type: EnemyType, behaviour: Behaviour) -> Self {
+        Enemy {
+            enemy_type,
+            behaviour,
+            direction: -1.,
+            sight_range: 96.,
+            hop_interval: 1.,
+            hop_timer: 1.,
+            stomped: false,
+        }
+    }
+}
+
+/// Draws the sprite at a set size and offset from the actor rather than as the art is laid out,
+/// on top of any squash and stretch
+#[derive(Component, Clone, Default, Debug)]
+pub struct SpriteFit {
+    pub size: Vec2,
+    pub anchor: Vec2,
+}
+
+/// Out of health, fading out before it is removed
+#[derive(Component, Clone, Debug)]
+pub struct Defeated {
+    pub timer: f32,
+    pub stomped: bool,
+}
+
+impl EnemyType {
+    pub fn from_identifier(identifier: &str) -> Option<EnemyType> {
+        match identifier {
+            "Sam" => Some(EnemyType::Sam),
+            "Dampboi" => Some(EnemyType::Dampboi),
+            _ => None,
+        }
+    }
+
+    pub fn default_behaviour(&self) -> Behaviour {
+        match self {
+            EnemyType::Sam => Behaviour::Patrol,
+            EnemyType::Dampboi => Behaviour::Hop,
+        }
+    }
+
+    pub fn contact_damage(&self) -> ContactDamage {
+        match self {
+            // Sam's scythe is in the way of anyone landing on top
+            EnemyType::Sam => ContactDamage {
+                stompable: false,
+                ..default()
+            },
+            EnemyType::Dampboi => ContactDamage {
+                bounce_speed: 240.,
+                ..default()
+            },
+        }
+    }
+
+    pub fn default_actor(&self) -> Actor {
+        match self {
+            EnemyType::Sam => Actor {
+                move_speed: 40.,
+                jump_speed: 150.,
+                jump_time: 0.1,
+                ..default()
+            },
+            EnemyType::Dampboi => Actor {
+                move_speed: 60.,
+                drag: 0.5,
+                jump_speed: 120.,
+                jump_time: 0.15,
+                ..default()
+            },
+        }
+    }
+}
+
+impl Behaviour {
+    pub fn from_identifier(identifier: &str) -> Option<Behaviour> {
+        match identifier {
+            "Patrol" => Some(Behaviour::Patrol),
+            "Chase" => Some(Behaviour::Chase),
+            "Hop" => Some(Behaviour::Hop),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Default, Bundle)]
+pub struct EnemyBundle {
+    pub sprite_sheet_bundle: SpriteSheetBundle,
+    pub sprite_animator: SpriteAnimator,
+    pub rigidbody: RigidBody,
+    pub collider: Collider,
+    pub sensor: Sensor,
+    pub label: Labeled,
+    pub controller: KinematicCharacterController,
+    pub actor: Actor,
+    pub actor_status: ActorStatus,
+    pub actor_anim: ActorAnimationStates,
+    pub actor_audio: ActorAudio,
+    pub squashy: Squashy,
+    pub sprite_fit: SpriteFit,
+    pub render_interpolation: RenderInterpolation,
+    pub health: Health,
+    pub damageable: Damageable,
+    pub contact_damage: ContactDamage,
+    pub enemy: Enemy,
+}
+
+impl EnemyBundle {
+    pub fn new(
+        enemy: Enemy,
+        actor: Actor,
+        health: Health,
+        asset_server: &AssetServer,
+        texture_atlases: &mut Assets<TextureAtlas>,
+    ) -> Self {
+        // Sam has a full sheet, Dampboi is a single large drawing shrunk down to size
+        let (texture_atlas, sprite_animator, actor_anim, sprite_fit) = match enemy.enemy_type {
+            EnemyType::Sam => (
+                TextureAtlas::from_grid(
+                    asset_server.load("sprites/sam1.png"),
+                    Vec2::new(48., 32.),
+                    4,
+                    4,
+                    None,
+                    None,
+                ),
+                SpriteAnimator::new(0, 3, 4, 0.2, true),
+                ActorAnimationStates {
+                    idle_row: 0,
+                    run_row: 1,
+                    jump_row: 2,
+                    fall_row: 3,
+                    idle_carry_row: 0,
+                    run_carry_row: 1,
+                    push_row: 1,
+                    yell_row: 0,
+                    wall_slide_row: 3,
+                    wall_jump_row: 2,
+                },
+                SpriteFit {
+                    size: Vec2::new(48., 32.),
+                    // The art leaves a few empty rows under Sam's feet
+                    anchor: Vec2::new(0., -0.1),
+                },
+            ),
+            EnemyType::Dampboi => (
+                TextureAtlas::from_grid(
+                    asset_server.load("sprites/dampboi.png"),
+                    Vec2::new(256., 256.),
+                    1,
+                    1,
+                    None,
+                    None,
+                ),
+                SpriteAnimator::new(0, 0, 1, 0.2, true),
+                ActorAnimationStates::default(),
+                SpriteFit {
+                    size: Vec2::new(16., 16.),
+                    anchor: Vec2::ZERO,
+                },
+            ),
+        };
+
+        EnemyBundle {
+            sprite_sheet_bundle: SpriteSheetBundle {
+                texture_atlas: texture_atlases.add(texture_atlas),
+                sprite: TextureAtlasSprite {
+                    custom_size: Some(sprite_fit.size),
+                    anchor: Anchor::Custom(sprite_fit.anchor),
+                    ..default()
+                },
+                transform: Transform::from_translation(Vec3::new(0., 0., 0.9)),
+                ..default()
+            },
+            sprite_animator,
+            rigidbody: RigidBody::KinematicPositionBased,
+            collider: Collider::capsule_y(5.0, 3.),
+            sensor: Sensor,
+            label: Labeled {
+                name: format!("{:?}", enemy.enemy_type).to_lowercase(),
+            },
+            controller: KinematicCharacterController {
+                offset: CharacterLength::Absolute(0.5),
+                autostep: None,
+                filter_flags: QueryFilterFlags::EXCLUDE_SENSORS,
+                ..default()
+            },
+            actor,
+            actor_status: ActorStatus {
+                last_dt: 1.,
+                facing_left: enemy.direction < 0.,
+                ..default()
+            },
+            actor_anim,
+            actor_audio: ActorAudio {
+                jump: asset_server.load("audio/jump1.ogg"),
+                land: asset_server.load("audio/land1.ogg"),
+                hit: asset_server.load("audio/hit.ogg"),
+                death: asset_server.load("audio/death1.ogg"),
+                wall_slide: asset_server.load("audio/land2.ogg"),
+                wall_jump: asset_server.load("audio/jump2.ogg"),
+                attack: asset_server.load("audio/attack1.ogg"),
+                ..default()
+            },
+            squashy: Squashy {
+                base_scale: sprite_fit.size,
+                ..enemy_squashy()
+            },
+            sprite_fit,
+            render_interpolation: RenderInterpolation::default(),
+            health,
+            damageable: Damageable,
+            contact_damage: enemy.enemy_type.contact_damage(),
+            enemy,
+        }
+    }
+}
+
+fn enemy_squashy() -> Squashy {
+    Squashy {
+        restore_time: 0.15,
+        squash_scale: Vec2::new(1.2, 0.8),
+        squash_time: 0.05,
+        stretch_scale: Vec2::new(0.8, 1.1),
+        stretch_time: 0.05,
+        from_pos: Vec2::ONE,
+        ..default()
+    }
+}
+
+impl LdtkEntity for EnemyBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        _layer_instance: &LayerInstance,
+        _tileset: Option<&Handle<Image>>,
+        _tileset_definition: Option<&TilesetDefinition>,
+        asset_server: &AssetServer,
+        texture_atlases: &mut Assets<TextureAtlas>,
+    ) -> Self {
+        let mut enemy_type = EnemyType::default();
+        let mut behaviour = None;
+        let mut speed = None;
+        let mut health = Health::new(2, 0.3);
+        let mut sight_range = None;
+        let mut hop_interval = None;
+        let mut facing_left = true;
+
+        for field in entity_instance.field_instances.iter() {
+            match field.identifier.as_str() {
+                "Type" => {
+                    if let FieldValue::String(Some(value)) = &field.value {
+                        match EnemyType::from_identifier(value) {
+                            Some(value) => enemy_type = value,
+                            None => println!("Unknown enemy type \"{}\"!", value),
+                        }
+                    }
+                }
+                "Behaviour" => {
+                    if let FieldValue::String(Some(value)) = &field.value {
+                        behaviour = Behaviour::from_identifier(value);
+                        if behaviour.is_none() && !value.is_empty() {
+                            println!("Unknown enemy behaviour \"{}\"!", value);
+                        }
+                    }
+                }
+                "Speed" => {
+                    if let FieldValue::Float(Some(value)) = field.value {
+                        speed = Some(value).filter(|speed| *speed > 0.);
+                    }
+                }
+                "Health" => {
+                    if let FieldValue::Int(Some(value)) = field.value {
+                        health = Health::new(value.max(1) as u32, health.invulnerable_time);
+                    }
+                }
+                "SightRange" => {
+                    if let FieldValue::Float(Some(value)) = field.value {
+                        sight_range = Some(value);
+                    }
+                }
+                "HopInterval" => {
+                    if let FieldValue::Float(Some(value)) = field.value {
+                        hop_interval = Some(value);
+                    }
+                }
+                "FacingLeft" => {
+                    if let FieldValue::Bool(value) = field.value {
+                        facing_left = value;
+                    }
+                }
+                unknown => println!("Unknown field \"{}\" on LDtk enemy object!", unknown),
+            }
+        }
+
+        let mut enemy = Enemy::new(
+            enemy_type,
+            behaviour.unwrap_or(enemy_type.default_behaviour()),
+        );
+        enemy.direction = if facing_left { -1. } else { 1. };
+        if let Some(sight_range) = sight_range {
+            enemy.sight_range = sight_range;
+        }
+        if let Some(hop_interval) = hop_interval {
+            enemy.hop_interval = hop_interval;
+            enemy.hop_timer = hop_interval;
+        }
+
+        let mut actor = enemy_type.default_actor();
+        if let Some(speed) = speed {
+            actor.move_speed = speed;
+        }
+
+        EnemyBundle::new(enemy, actor, health, asset_server, texture_atlases)
+    }
+}
+
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, enemy_inputs
+                .after(actor_status)
+                .after(player_inputs)
+                .before(actor_movement)
+                .in_set(SimulationSet)
+                .run_if(in_state(GameState::Playing))
+            )
+            .add_systems(FixedUpdate, enemy_contacts
+                .after(actor_movement)
+                .before(apply_damage)
+                .in_set(SimulationSet)
+                .run_if(in_state(GameState::Playing))
+            )
+            .add_systems(FixedUpdate, (defeat_enemies, remove_defeated_enemies)
+                .chain()
+                .after(apply_damage)
+                .in_set(SimulationSet)
+                .run_if(in_state(GameState::Playing))
+            )
+            ;
+    }
+}
+
+pub struct EnemyPresentationPlugin;
+
+impl Plugin for EnemyPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+            defeat_animation.after(invulnerability_flicker),
+            fit_sprites.after(squash_animation),
+            squash_stomped_enemies,
+        ).run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// Steers enemies by filling in their `Actor` inputs, the same ones `player_inputs` fills for players
+pub fn enemy_inputs(
+    fixed_time: Res<FixedTime>,
+    rapier_context: Res<RapierContext>,
+    player_query: Query<&Transform, (With<Player>, Without<Dying>)>,
+    mut enemy_query: Query<(
+        Entity,
+        &Transform,
+        &ActorStatus,
+        &mut Actor,
+        &mut Enemy,
+        Option<&Defeated>,
+    )>,
+) {
+    let dt = fixed_time.period.as_secs_f32();
+
+    for (entity, transform, status, mut actor, mut enemy, defeated) in &mut enemy_query {
+        actor.move_input = 0.;
+        actor.aim_input = Vec2::ZERO;
+        actor.jump_input = false;
+
+        if defeated.is_some() || status.hitstun > 0. {
+            continue;
+        }
+
+        let position = transform.translation.truncate();
+
+        let target = match enemy.behaviour {
+            Behaviour::Chase => player_query
+                .iter()
+                .map(|player| player.translation.truncate())
+                .filter(|player| player.distance(position) <= enemy.sight_range)
+                .min_by(|a, b| a.distance(position).total_cmp(&b.distance(position))),
+            _ => None,
+        };
+
+        if let Some(target) = target {
+            // Stays put once underneath or on top of its target rather than jittering
+            if (target.x - position.x).abs() > 2. {
+                enemy.direction = (target.x - position.x).signum();
+            }
+        }
+
+        let wall_ahead = if enemy.direction > 0. {
+            status.right_wall
+        } else {
+            status.left_wall
+        };
+        let ledge_ahead = status.grounded
+            && !ground_ahead(&rapier_context, entity, position, enemy.direction);
+
+        match enemy.behaviour {
+            Behaviour::Chase if target.is_some() => {
+                // Hops small obstacles on the way but waits at ledges for the player to come back
+                if !ledge_ahead {
+                    actor.move_input = enemy.direction;
+                }
+                actor.jump_input = wall_ahead && status.grounded;
+            }
+            Behaviour::Patrol | Behaviour::Chase => {
+                if wall_ahead || ledge_ahead {
+                    enemy.direction = -enemy.direction;
+                }
+                actor.move_input = enemy.direction;
+            }
+            Behaviour::Hop => {
+                if wall_ahead {
+                    enemy.direction = -enemy.direction;
+                }
+
+                if status.grounded {
+                    enemy.hop_timer -= dt;
+                    if enemy.hop_timer <= 0. {
+                        enemy.hop_timer = enemy.hop_interval;
+                        actor.jump_input = true;
+                    }
+                } else {
+                    actor.move_input = enemy.direction;
+                }
+            }
+        }
+
+        // Holds the jump for its full height once it has started
+        if matches!(status.jump_state, JumpState::Ascending(_)) {
+            actor.jump_input = true;
+        }
+    }
+}
+
+/// Whether there is something solid to walk onto just in front of an enemy's feet
+fn ground_ahead(rapier_context: &RapierContext, entity: Entity, position: Vec2, direction: f32) -> bool {
+    let origin = position + Vec2::new(direction * LEDGE_LOOKAHEAD, 0.);
+    let filter = QueryFilter::new()
+        .exclude_sensors()
+        .exclude_collider(entity);
+
+    rapier_context
+        .cast_ray(origin, Vec2::NEG_Y, 12., true, filter)
+        .is_some()
+}
+
+/// Players landing on a stompable enemy defeat it and bounce off, any other touch hurts them
+fn enemy_contacts(
+    rapier_context: Res<RapierContext>,
+    mut hit_stop: ResMut<HitStop>,
+    mut damage_writer: EventWriter<DamageEvent>,
+    mut event_writer: EventWriter<ActorTickEvent>,
+    mut player_query: Query<
+        (Entity, &Transform, &Health, &mut ActorStatus),
+        (With<Player>, Without<Dying>),
+    >,
+    mut enemy_query: Query<(&Transform, &Health, &ContactDamage, &mut Enemy), Without<Defeated>>,
+) {
+    for (player, transform, health, mut status) in &mut player_query {
+        let shape = Collider::capsule_y(5.5, 3.5);
+        let filter = QueryFilter::new().exclude_collider(player);
+        let shape_pos = transform.translation.truncate();
+
+        let mut touched = None;
+        rapier_context.intersections_with_shape(shape_pos, 0., &shape, filter, |entity| -> bool {
+            if enemy_query.contains(entity) {
+                touched = Some(entity);
+                return false;
+            }
+            true
+        });
+
+        let Some(touched) = touched else {
+            continue;
+        };
+        let Ok((enemy_transform, enemy_health, contact, mut enemy)) =
+            enemy_query.get_mut(touched)
+        else {
+            continue;
+        };
+        if enemy_health.is_dead() {
+            continue;
+        }
+
+        let offset = shape_pos - enemy_transform.translation.truncate();
+
+        if contact.stompable && status.velocity.y < 0. && offset.y > STOMP_HEIGHT {
+            // Still bounces off an enemy that was just hit, it just doesn't go down yet
+            if !enemy_health.is_invulnerable() {
+                enemy.stomped = true;
+                damage_writer.send(DamageEvent {
+                    target: touched,
+                    source: Some(player),
+                    damage: enemy_health.current,
+                    knockback: Vec2::ZERO,
+                });
+            }
+
+            status.launch = Some(Vec2::new(status.velocity.x, contact.bounce_speed));
+            event_writer.send(ActorTickEvent {
+                actor: player,
+                event: ActorEvent::Launched,
+            });
+            hit_stop.ticks = hit_stop.ticks.max(contact.hit_stop_ticks);
+        } else if !health.is_invulnerable() && !health.is_dead() {
+            // Thrown back away from the enemy, or the way the player came when right on top of it
+            let away = if offset.x.abs() > 1. {
+                offset.x.signum()
+            } else if status.facing_left {
+                1.
+            } else {
+                -1.
+            };
+
+            damage_writer.send(DamageEvent {
+                target: player,
+                source: Some(touched),
+                damage: contact.damage,
+                knockback: Vec2::new(away * contact.knockback, contact.knockback),
+            });
+        }
+    }
+}
+
+fn defeat_enemies(
+    mut commands: Commands,
+    enemy_query: Query<(Entity, &Health, &Enemy), (Changed<Health>, Without<Defeated>)>,
+) {
+    for (entity, health, enemy) in &enemy_query {
+        if !health.is_dead() {
+            continue;
+        }
+
+        commands
+            .entity(entity)
+            .insert(Defeated {
+                timer: DEFEAT_TIME,
+                stomped: enemy.stomped,
+            })
+            .remove::<Damageable>();
+    }
+}
+
+fn remove_defeated_enemies(
+    mut commands: Commands,
+    fixed_time: Res<FixedTime>,
+    mut enemy_query: Query<(Entity, &mut Defeated)>,
+) {
+    let dt = fixed_time.period.as_secs_f32();
+
+    for (entity, mut defeated) in &mut enemy_query {
+        defeated.timer -= dt;
+        if defeated.timer <= 0. {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Flips the enemy over and fades it out, stomped enemies are squashed flat instead
+fn defeat_animation(mut enemy_query: Query<(&Defeated, &mut TextureAtlasSprite)>) {
+    for (defeated, mut sprite) in &mut enemy_query {
+        sprite.flip_y = !defeated.stomped;
+        sprite.color.set_a((defeated.timer / DEFEAT_TIME).clamp(0., 1.));
+    }
+}
+
+/// Squash and stretch leaves sprites at their own size when it isn't playing, and centered
+fn fit_sprites(mut sprite_query: Query<(&SpriteFit, &mut TextureAtlasSprite)>) {
+    for (fit, mut sprite) in &mut sprite_query {
+        if sprite.custom_size.is_none() {
+            sprite.custom_size = Some(fit.size);
+        }
+
+        sprite.anchor = match sprite.anchor {
+            Anchor::Custom(offset) => Anchor::Custom(offset + fit.anchor),
+            _ => Anchor::Custom(fit.anchor),
+        };
+    }
+}
+
+fn squash_stomped_enemies(mut enemy_query: Query<(&Defeated, &mut Squashy), Added<Defeated>>) {
+    for (defeated, mut squish) in &mut enemy_query {
+        if !defeated.stomped {
+            continue;
+        }
+
+        // Held flat for as long as the enemy sticks around
+        squish.squash_scale = Vec2::new(1.5, 0.3);
+        squish.squash_time = DEFEAT_TIME;
+        squish.change_state(Some(SquashStretchState::Squash));
+    }
+}
@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+pub struct FixedStepPlugin;
+
+/// Length of one simulation tick, the same on every machine regardless of display rate
+pub const FIXED_TIMESTEP: f32 = 1. / 60.;
+
+/// Gameplay systems that run each tick in `FixedUpdate`, ahead of the physics step
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
+pub struct SimulationSet;
+
+/// Freezes the simulation for a moment to give a big hit some weight.
+/// Presentation keeps running, so squash and stretch plays out over the pause.
+#[derive(Resource, Default, Debug)]
+pub struct HitStop {
+    /// Ticks left to skip, starting from the next one
+    pub ticks: u32,
+    /// Whether the current tick is skipped, only decided at the start of a tick
+    frozen: bool,
+}
+
+/// Entities moved by the simulation have their `Transform` smoothed between the last two ticks
+/// when drawn, so motion stays smooth on displays faster or slower than the tick rate.
+/// The smoothing itself is part of the presentation, see `CameraPlugin`.
+#[derive(Component, Default, Clone)]
+pub struct RenderInterpolation {
+    previous: Vec3,
+    current: Vec3,
+    rendered: Option<Vec3>,
+}
+
+impl RenderInterpolation {
+    /// Draws the entity at its new position right away after being moved during a tick,
+    /// instead of sliding there from where it was
+    pub fn snap(&mut self, translation: Vec3) {
+        self.previous = translation;
+    }
+}
+
+/// Rapier is set up without its default systems, they are added here to step in time with the
+/// simulation instead of once per frame in `PostUpdate`
+impl Plugin for FixedStepPlugin {
+    fn build(&self, app: &mut App) {
+        type Physics = RapierPhysicsPlugin<NoUserData>;
+
+        app.insert_resource(FixedTime::new_from_secs(FIXED_TIMESTEP))
+            .init_resource::<HitStop>()
+            .configure_sets(
+                FixedUpdate,
+                (
+                    SimulationSet,
+                    PhysicsSet::SyncBackend,
+                    PhysicsSet::SyncBackendFlush,
+                    PhysicsSet::StepSimulation,
+                    PhysicsSet::Writeback,
+                )
+                    .chain(),
+            )
+            // Physics waits out a hit-stop along with everything else, so nothing drifts
+            .configure_set(FixedUpdate, SimulationSet.run_if(not_hit_stopped))
+            .configure_set(FixedUpdate, PhysicsSet::SyncBackend.run_if(not_hit_stopped))
+            .configure_set(FixedUpdate, PhysicsSet::SyncBackendFlush.run_if(not_hit_stopped))
+            .configure_set(FixedUpdate, PhysicsSet::StepSimulation.run_if(not_hit_stopped))
+            .configure_set(FixedUpdate, PhysicsSet::Writeback.run_if(not_hit_stopped))
+            .add_systems(
+                FixedUpdate,
+                (
+                    Physics::get_systems(PhysicsSet::SyncBackend).in_set(PhysicsSet::SyncBackend),
+                    Physics::get_systems(PhysicsSet::SyncBackendFlush)
+                        .in_set(PhysicsSet::SyncBackendFlush),
+                    Physics::get_systems(PhysicsSet::StepSimulation)
+                        .in_set(PhysicsSet::StepSimulation),
+                    Physics::get_systems(PhysicsSet::Writeback).in_set(PhysicsSet::Writeback),
+                ),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    begin_fixed_step.before(SimulationSet),
+                    count_down_hit_stop.before(SimulationSet),
+                    end_fixed_step.after(PhysicsSet::Writeback),
+                ),
+            );
+    }
+}
+
+fn count_down_hit_stop(mut hit_stop: ResMut<HitStop>) {
+    hit_stop.frozen = hit_stop.ticks > 0;
+    if hit_stop.frozen {
+        hit_stop.ticks -= 1;
+    }
+}
+
+fn not_hit_stopped(hit_stop: Res<HitStop>) -> bool {
+    !hit_stop.frozen
+}
+
+/// Puts the simulated position back before a tick.
+/// Anything else that moved the entity since the last tick, like a respawn, is kept instead.
+fn begin_fixed_step(mut query: Query<(&mut Transform, &mut RenderInterpolation)>) {
+    for (mut transform, mut interpolation) in &mut query {
+        if interpolation.rendered == Some(transform.translation) {
+            transform.translation = interpolation.current;
+        }
+
+        interpolation.rendered = None;
+        interpolation.previous = transform.translation;
+    }
+}
+
+fn end_fixed_step(mut query: Query<(&Transform, &mut RenderInterpolation)>) {
+    for (transform, mut interpolation) in &mut query {
+        interpolation.current = transform.translation;
+    }
+}
+
+pub fn interpolate_transforms(
+    fixed_time: Res<FixedTime>,
+    mut query: Query<(&mut Transform, &mut RenderInterpolation)>,
+) {
+    let alpha = (fixed_time.accumulated().as_secs_f32() / fixed_time.period.as_secs_f32()).min(1.);
+
+    for (mut transform, mut interpolation) in &mut query {
+        let expected = interpolation.rendered.unwrap_or(interpolation.current);
+        if transform.translation != expected {
+            continue;
+        }
+
+        let smoothed = interpolation.previous.lerp(interpolation.current, alpha);
+        transform.translation = smoothed;
+        interpolation.rendered = Some(smoothed);
+    }
+}
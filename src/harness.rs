@@ -0,0 +1,478 @@
+//! Runs the simulation without a window, renderer or audio device, for gameplay tests.
+//!
+//! Tests script each player's `Actions` tick by tick and look at the resulting `ActorStatus`,
+//! and at the `ChangeLevelEvent`s and `ReloadWorldEvent`s sent along the way:
+//!
+//! ```ignore
+//! let mut harness = SimulationHarness::new("levels/World.ldtk", 0);
+//! harness.run_until(180, |harness| harness.player_status(0).grounded);
+//! harness.run_script(0, &[(30, PlayerActions { movement: Vec2::X, ..default() })]);
+//! ```
+
+use std::time::{Duration, Instant};
+
+use bevy::{
+    asset::AssetPlugin,
+    hierarchy::HierarchyPlugin,
+    input::InputPlugin,
+    prelude::*,
+    render::render_resource::Shader,
+    time::TimeUpdateStrategy,
+    transform::TransformPlugin,
+};
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::actions::{Actions, LocalPlayers, PlayerActions, PlayerSlot};
+use crate::actor::{Actor, ActorEvent, ActorStatus, ActorTickEvent};
+use crate::checkpoint::Checkpoint;
+use crate::cuttable::{Cuttable, WheatBundle};
+use crate::door::{Door, LevelSouls};
+use crate::enemy::{Behaviour, Enemy, EnemyBundle, EnemyType};
+use crate::fixed_step::{SimulationSet, FIXED_TIMESTEP};
+use crate::health::{DamageEvent, Hazard, Health};
+use crate::loading::LevelAssets;
+use crate::pickup::{Pickup, PickupType};
+use crate::player::{Dying, Player, PlayerBundle};
+use crate::prop::{BlockBundle, PropBundle, PropType, Pushable};
+use crate::world::{ChangeLevelEvent, ReloadWorldEvent};
+use crate::yell::YellEvent;
+use crate::{GameState, SimulationPlugin};
+
+/// How long the LDtk file gets to load and spawn its players
+const LOAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Updates allowed for a single tick before the harness assumes the simulation is stuck
+const MAX_UPDATES_PER_TICK: usize = 10;
+
+pub struct SimulationHarness {
+    pub app: App,
+}
+
+/// Ticks run since the level was ready, and the events seen on each of them
+#[derive(Resource, Default)]
+struct HarnessLog {
+    tick: u32,
+    level_changes: Vec<(u32, ChangeLevelEvent)>,
+    reloads: Vec<u32>,
+    yells: Vec<(u32, YellEvent)>,
+    actor_events: Vec<(u32, Entity, ActorEvent)>,
+}
+
+impl SimulationHarness {
+    /// Loads an LDtk file from the assets folder and plays the level until its players have spawned
+    pub fn new(ldtk_path: &str, level: usize) -> Self {
+        let mut app = App::new();
+
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            TransformPlugin,
+            HierarchyPlugin,
+            InputPlugin,
+            ImagePlugin::default_nearest(),
+        ))
+        // Asset types the LDtk and physics plugins expect the renderer to have set up
+        .add_asset::<TextureAtlas>()
+        .add_asset::<Mesh>()
+        .add_asset::<Shader>()
+        // Every update is exactly one tick, however long it really took
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            FIXED_TIMESTEP,
+        )))
+        .init_resource::<HarnessLog>()
+        .add_plugins(SimulationPlugin)
+        .add_systems(FixedUpdate, count_tick.before(SimulationSet))
+        .add_systems(FixedUpdate, log_events.after(SimulationSet));
+
+        app.finish();
+        app.cleanup();
+
+        let world = app.world.resource::<AssetServer>().load(ldtk_path);
+        app.insert_resource(LevelAssets { world })
+            .insert_resource(LevelSelection::Index(level));
+        app.world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Playing);
+
+        let mut harness = SimulationHarness { app };
+
+        let start = Instant::now();
+        while harness.player_entity(0).is_none() {
+            assert!(
+                start.elapsed() < LOAD_TIMEOUT,
+                "level {} of {} never spawned a player",
+                level,
+                ldtk_path
+            );
+            harness.app.update();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        *harness.app.world.resource_mut::<HarnessLog>() = HarnessLog::default();
+        harness
+    }
+
+    /// Ticks run since the level was ready
+    pub fn ticks(&self) -> u32 {
+        self.app.world.resource::<HarnessLog>().tick
+    }
+
+    /// Runs exactly one simulation tick
+    pub fn tick(&mut self) {
+        let tick = self.ticks();
+
+        for _ in 0..MAX_UPDATES_PER_TICK {
+            self.app.update();
+            if self.ticks() != tick {
+                return;
+            }
+        }
+
+        panic!("the simulation stopped ticking after tick {}", tick);
+    }
+
+    pub fn run(&mut self, ticks: u32) {
+        for _ in 0..ticks {
+            self.tick();
+        }
+    }
+
+    /// Ticks until the condition holds, returning the tick it did, or `None` after `max_ticks`
+    pub fn run_until(
+        &mut self,
+        max_ticks: u32,
+        mut condition: impl FnMut(&mut SimulationHarness) -> bool,
+    ) -> Option<u32> {
+        for _ in 0..max_ticks {
+            self.tick();
+            if condition(self) {
+                return Some(self.ticks());
+            }
+        }
+
+        None
+    }
+
+    /// Sets what the player does from the next tick on.
+    /// Presses are latched like real input, so they only count on the first of those ticks.
+    pub fn set_actions(&mut self, player: usize, player_actions: PlayerActions) {
+        let mut actions = self.app.world.resource_mut::<Actions>();
+        if actions.players.len() <= player {
+            actions.players.resize(player + 1, PlayerActions::default());
+        }
+
+        actions.players[player] = player_actions;
+    }
+
+    /// Plays each set of actions for the given number of ticks, in order
+    pub fn run_script(&mut self, player: usize, script: &[(u32, PlayerActions)]) {
+        for (ticks, player_actions) in script {
+            self.set_actions(player, *player_actions);
+            self.run(*ticks);
+        }
+    }
+
+    pub fn player_entity(&mut self, index: usize) -> Option<Entity> {
+        self.app
+            .world
+            .query::<(Entity, &Player)>()
+            .iter(&self.app.world)
+            .find(|(_, player)| player.index == index)
+            .map(|(entity, _)| entity)
+    }
+
+    fn expect_player(&mut self, index: usize) -> Entity {
+        self.player_entity(index)
+            .unwrap_or_else(|| panic!("there is no player {}", index))
+    }
+
+    pub fn player_status(&mut self, index: usize) -> ActorStatus {
+        let entity = self.expect_player(index);
+        self.app.world.get::<ActorStatus>(entity).cloned().unwrap_or_default()
+    }
+
+    pub fn player_health(&mut self, index: usize) -> Health {
+        let entity = self.expect_player(index);
+        self.app.world.get::<Health>(entity).cloned().unwrap_or_default()
+    }
+
+    /// Whether the player is out of health and waiting to respawn
+    pub fn player_dying(&mut self, index: usize) -> bool {
+        let entity = self.expect_player(index);
+        self.app.world.get::<Dying>(entity).is_some()
+    }
+
+    pub fn player_position(&mut self, index: usize) -> Vec2 {
+        let entity = self.expect_player(index);
+        self.app.world.get::<Transform>(entity).unwrap().translation.truncate()
+    }
+
+    pub fn set_player_position(&mut self, index: usize, position: Vec2) {
+        let entity = self.expect_player(index);
+        let mut transform = self.app.world.get_mut::<Transform>(entity).unwrap();
+        transform.translation = position.extend(transform.translation.z);
+    }
+
+    /// Joins another local player, as if a gamepad had connected
+    pub fn join_player(&mut self, index: usize) {
+        let mut local_players = self.app.world.resource_mut::<LocalPlayers>();
+        if local_players.slots.len() <= index {
+            local_players.slots.resize(index + 1, None);
+        }
+
+        local_players.slots[index] = Some(PlayerSlot::default());
+    }
+
+    /// Leaves a local player's slot, as if their gamepad had disconnected
+    pub fn leave_player(&mut self, index: usize) {
+        let mut local_players = self.app.world.resource_mut::<LocalPlayers>();
+        if let Some(slot) = local_players.slots.get_mut(index) {
+            *slot = None;
+        }
+    }
+
+    /// Adds a player the way a level places one, set up like the first player, without joining them
+    pub fn spawn_player(&mut self, index: usize, position: Vec2) -> Entity {
+        let first = self.expect_player(0);
+        let actor = self.app.world.get::<Actor>(first).unwrap().clone();
+        let health = self.app.world.get::<Health>(first).unwrap().clone();
+        let z = self.app.world.get::<Transform>(first).unwrap().translation.z;
+
+        let asset_server = self.app.world.resource::<AssetServer>().clone();
+        let mut texture_atlases = self.app.world.resource_mut::<Assets<TextureAtlas>>();
+        let mut bundle = PlayerBundle::new(
+            index,
+            "sprites/baby_jake_2.png",
+            actor,
+            health,
+            &asset_server,
+            &mut texture_atlases,
+        );
+        bundle.sprite_sheet_bundle.transform.translation = position.extend(z);
+
+        self.app.world.spawn(bundle).id()
+    }
+
+    /// Hurts the player on the next tick, without any knockback
+    pub fn hurt_player(&mut self, index: usize, damage: u32) {
+        let target = self.expect_player(index);
+        self.app.world.send_event(DamageEvent {
+            target,
+            source: None,
+            damage,
+            knockback: Vec2::ZERO,
+        });
+    }
+
+    pub fn position(&self, entity: Entity) -> Vec2 {
+        self.app.world.get::<Transform>(entity).unwrap().translation.truncate()
+    }
+
+    pub fn status(&self, entity: Entity) -> ActorStatus {
+        self.app.world.get::<ActorStatus>(entity).cloned().unwrap_or_default()
+    }
+
+    pub fn health(&self, entity: Entity) -> Option<Health> {
+        self.app.world.get::<Health>(entity).cloned()
+    }
+
+    /// Whether the entity is still around, enemies are removed some time after being defeated
+    pub fn exists(&self, entity: Entity) -> bool {
+        self.app.world.get_entity(entity).is_some()
+    }
+
+    /// Every `ChangeLevelEvent` so far, with the tick it was sent on
+    pub fn level_changes(&self) -> &[(u32, ChangeLevelEvent)] {
+        &self.app.world.resource::<HarnessLog>().level_changes
+    }
+
+    /// The ticks a `ReloadWorldEvent` was sent on
+    pub fn reloads(&self) -> &[u32] {
+        &self.app.world.resource::<HarnessLog>().reloads
+    }
+
+    /// Every `YellEvent` so far, with the tick it was sent on
+    pub fn yells(&self) -> &[(u32, YellEvent)] {
+        &self.app.world.resource::<HarnessLog>().yells
+    }
+
+    /// Every `ActorEvent` sent for one player so far, with the tick it was sent on
+    pub fn player_events(&mut self, index: usize) -> Vec<(u32, ActorEvent)> {
+        let entity = self.expect_player(index);
+        self.app
+            .world
+            .resource::<HarnessLog>()
+            .actor_events
+            .iter()
+            .filter(|(_, actor, _)| *actor == entity)
+            .map(|(tick, _, event)| (*tick, event.clone()))
+            .collect()
+    }
+
+    /// Adds an open door, for levels that don't have one where a test needs it
+    pub fn spawn_door(&mut self, position: Vec2, next_level: usize) -> Entity {
+        self.spawn_locked_door(position, next_level, 0)
+    }
+
+    /// Adds a door that opens once `required_souls` souls are collected in the level
+    pub fn spawn_locked_door(&mut self, position: Vec2, next_level: usize, required_souls: usize) -> Entity {
+        self.app
+            .world
+            .spawn((
+                Door {
+                    next_level,
+                    required_souls,
+                    ..default()
+                },
+                Collider::cuboid(8., 16.),
+                Sensor,
+                TransformBundle::from_transform(Transform::from_translation(position.extend(0.))),
+            ))
+            .id()
+    }
+
+    pub fn spawn_checkpoint(&mut self, position: Vec2) -> Entity {
+        self.app
+            .world
+            .spawn((
+                Checkpoint::default(),
+                Collider::cuboid(8., 16.),
+                Sensor,
+                TransformBundle::from_transform(Transform::from_translation(position.extend(0.))),
+            ))
+            .id()
+    }
+
+    /// Adds a hazard that deals `damage` on touch, unlike the level's spikes which always kill
+    pub fn spawn_hazard(&mut self, position: Vec2, damage: u32) -> Entity {
+        self.app
+            .world
+            .spawn((
+                Hazard {
+                    damage,
+                    ..default()
+                },
+                Collider::cuboid(8., 8.),
+                Sensor,
+                TransformBundle::from_transform(Transform::from_translation(position.extend(0.))),
+            ))
+            .id()
+    }
+
+    pub fn spawn_prop(&mut self, prop_type: PropType, position: Vec2) -> Entity {
+        let asset_server = self.app.world.resource::<AssetServer>().clone();
+        let mut bundle = PropBundle::new(prop_type, prop_type.default_sprite_path(), &asset_server);
+        bundle.sprite_bundle.transform.translation = position.extend(0.8);
+
+        self.app.world.spawn(bundle).id()
+    }
+
+    pub fn spawn_block(&mut self, position: Vec2) -> Entity {
+        let asset_server = self.app.world.resource::<AssetServer>().clone();
+        let mut bundle = BlockBundle::new(Pushable::default(), &asset_server);
+        bundle.sprite_bundle.transform.translation = position.extend(0.8);
+
+        self.app.world.spawn(bundle).id()
+    }
+
+    pub fn spawn_enemy(
+        &mut self,
+        enemy_type: EnemyType,
+        behaviour: Behaviour,
+        position: Vec2,
+    ) -> Entity {
+        let asset_server = self.app.world.resource::<AssetServer>().clone();
+        let mut texture_atlases = self.app.world.resource_mut::<Assets<TextureAtlas>>();
+        let mut bundle = EnemyBundle::new(
+            Enemy::new(enemy_type, behaviour),
+            enemy_type.default_actor(),
+            Health::new(2, 0.3),
+            &asset_server,
+            &mut texture_atlases,
+        );
+        bundle.sprite_sheet_bundle.transform.translation = position.extend(0.9);
+
+        self.app.world.spawn(bundle).id()
+    }
+
+    pub fn spawn_wheat(&mut self, position: Vec2) -> Entity {
+        let asset_server = self.app.world.resource::<AssetServer>().clone();
+        let mut bundle = WheatBundle::new(Cuttable::default(), &asset_server);
+        bundle.sprite_bundle.transform.translation = position.extend(0.5);
+
+        self.app.world.spawn(bundle).id()
+    }
+
+    pub fn is_cut(&self, entity: Entity) -> bool {
+        self.app.world.get::<Cuttable>(entity).is_some_and(Cuttable::is_cut)
+    }
+
+    pub fn spawn_pickup(&mut self, pickup_type: PickupType, position: Vec2) -> Entity {
+        self.app
+            .world
+            .spawn((
+                Pickup {
+                    pickup_type: Some(pickup_type),
+                    ..default()
+                },
+                Collider::ball(4.),
+                Sensor,
+                TransformBundle::from_transform(Transform::from_translation(position.extend(0.))),
+            ))
+            .id()
+    }
+
+    /// Souls collected in the current level
+    pub fn souls_collected(&self) -> usize {
+        self.app.world.resource::<LevelSouls>().collected
+    }
+
+    pub fn pickup_count(&mut self) -> usize {
+        self.app
+            .world
+            .query_filtered::<(), With<Pickup>>()
+            .iter(&self.app.world)
+            .count()
+    }
+
+    /// Positions of everything that hurts players on touch
+    pub fn hazard_positions(&mut self) -> Vec<Vec2> {
+        self.app
+            .world
+            .query_filtered::<&GlobalTransform, With<Hazard>>()
+            .iter(&self.app.world)
+            .map(|transform| transform.translation().truncate())
+            .collect()
+    }
+}
+
+fn count_tick(mut log: ResMut<HarnessLog>) {
+    log.tick += 1;
+}
+
+fn log_events(
+    mut log: ResMut<HarnessLog>,
+    mut change_events: EventReader<ChangeLevelEvent>,
+    mut reload_events: EventReader<ReloadWorldEvent>,
+    mut yell_events: EventReader<YellEvent>,
+    mut actor_events: EventReader<ActorTickEvent>,
+) {
+    let tick = log.tick;
+
+    for ev in change_events.iter() {
+        log.level_changes.push((tick, ev.clone()));
+    }
+
+    for _ in reload_events.iter() {
+        log.reloads.push(tick);
+    }
+
+    for ev in yell_events.iter() {
+        log.yells.push((tick, ev.clone()));
+    }
+
+    for ev in actor_events.iter() {
+        log.actor_events.push((tick, ev.actor, ev.event.clone()));
+    }
+}
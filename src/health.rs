@@ -0,0 +1,272 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    actor::{actor_movement, ActorEvent, ActorStatus, ActorTickEvent},
+    fixed_step::SimulationSet,
+    pickup::{check_for_pickups, PickupEvent, PickupType},
+    settings::Settings,
+    world::Labeled,
+    GameState,
+};
+
+pub struct HealthPlugin;
+
+/// How long each blink lasts while invulnerable
+const FLICKER_PERIOD: f32 = 0.08;
+
+#[derive(Component, Clone, Debug)]
+pub struct Health {
+    pub current: u32,
+    pub max: u32,
+    /// Length of the invulnerability after taking a hit
+    pub invulnerable_time: f32,
+    /// Invulnerability left from the last hit
+    pub invulnerable: f32,
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Health::new(3, 1.)
+    }
+}
+
+impl Health {
+    pub fn new(max: u32, invulnerable_time: f32) -> Self {
+        Health {
+            current: max,
+            max,
+            invulnerable_time,
+            invulnerable: 0.,
+        }
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable > 0.
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current == 0
+    }
+}
+
+/// Hurts anything with `Health` that touches it
+#[derive(Component, Clone, Debug)]
+pub struct Hazard {
+    pub damage: u32,
+    /// Speed the victim is thrown up and away at
+    pub knockback: f32,
+}
+
+impl Default for Hazard {
+    fn default() -> Self {
+        Hazard {
+            damage: 1,
+            knockback: 150.,
+        }
+    }
+}
+
+/// Can be hit by attacks, which send it a `DamageEvent`
+#[derive(Component, Clone, Default, Debug)]
+pub struct Damageable;
+
+#[derive(Event, Clone, Debug)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub source: Option<Entity>,
+    pub damage: u32,
+    /// Velocity the target takes on from the hit
+    pub knockback: Vec2,
+}
+
+#[derive(Clone, Default, Bundle)]
+pub struct HazardBundle {
+    pub collider: Collider,
+    pub sensor: Sensor,
+    pub label: Labeled,
+    pub hazard: Hazard,
+}
+
+impl LdtkEntity for HazardBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        _layer_instance: &LayerInstance,
+        _tileset: Option<&Handle<Image>>,
+        _tileset_definition: Option<&TilesetDefinition>,
+        _asset_server: &AssetServer,
+        _texture_atlases: &mut Assets<TextureAtlas>,
+    ) -> Self {
+        let mut hazard = Hazard::default();
+
+        for field in entity_instance.field_instances.iter() {
+            match field.identifier.as_str() {
+                "Damage" => {
+                    if let FieldValue::Int(Some(value)) = field.value {
+                        hazard.damage = value.max(0) as u32;
+                    }
+                }
+                "Knockback" => {
+                    if let FieldValue::Float(Some(value)) = field.value {
+                        hazard.knockback = value;
+                    }
+                }
+                unknown => println!("Unknown field \"{}\" on LDtk hazard object!", unknown),
+            }
+        }
+
+        HazardBundle {
+            collider: Collider::cuboid(
+                entity_instance.width as f32 / 2.,
+                entity_instance.height as f32 / 2.,
+            ),
+            sensor: Sensor,
+            label: Labeled {
+                name: String::from("hazard"),
+            },
+            hazard,
+        }
+    }
+}
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageEvent>()
+            .add_systems(FixedUpdate, (
+                tick_invulnerability,
+                touch_hazards,
+                apply_damage,
+            ).chain().after(actor_movement).in_set(SimulationSet).run_if(in_state(GameState::Playing)))
+            .add_systems(FixedUpdate, heal_from_pickups
+                .after(check_for_pickups)
+                .in_set(SimulationSet)
+                .run_if(in_state(GameState::Playing))
+            )
+            ;
+    }
+}
+
+pub struct HealthPresentationPlugin;
+
+impl Plugin for HealthPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, invulnerability_flicker.run_if(in_state(GameState::Playing)));
+    }
+}
+
+fn tick_invulnerability(fixed_time: Res<FixedTime>, mut health_query: Query<&mut Health>) {
+    let dt = fixed_time.period.as_secs_f32();
+
+    for mut health in &mut health_query {
+        // Left alone once dead so the death is only seen as a change once
+        if health.is_invulnerable() && !health.is_dead() {
+            health.invulnerable = (health.invulnerable - dt).max(0.);
+        }
+    }
+}
+
+fn touch_hazards(
+    mut damage_writer: EventWriter<DamageEvent>,
+    rapier_context: Res<RapierContext>,
+    victim_query: Query<(Entity, &Transform, &Health, &ActorStatus)>,
+    hazard_query: Query<(&Hazard, &GlobalTransform)>,
+) {
+    for (victim, transform, health, status) in &victim_query {
+        if health.is_invulnerable() || health.is_dead() {
+            continue;
+        }
+
+        let shape = Collider::capsule_y(5.5, 3.5);
+        let filter = QueryFilter::new();
+        let shape_pos = transform.translation.truncate();
+
+        rapier_context.intersections_with_shape(shape_pos, 0., &shape, filter, |entity| -> bool {
+            let Ok((hazard, hazard_transform)) = hazard_query.get(entity) else {
+                return true;
+            };
+
+            // Thrown back the way the victim came, or away from the hazard's center
+            let offset = shape_pos.x - hazard_transform.translation().x;
+            let away = if offset.abs() > 1. {
+                offset.signum()
+            } else if status.facing_left {
+                1.
+            } else {
+                -1.
+            };
+
+            damage_writer.send(DamageEvent {
+                target: victim,
+                source: Some(entity),
+                damage: hazard.damage,
+                knockback: Vec2::new(away * hazard.knockback, hazard.knockback),
+            });
+
+            false // one hit at a time
+        });
+    }
+}
+
+pub fn apply_damage(
+    mut damage_events: EventReader<DamageEvent>,
+    mut event_writer: EventWriter<ActorTickEvent>,
+    mut target_query: Query<(&mut Health, &mut ActorStatus)>,
+) {
+    for ev in damage_events.iter() {
+        let Ok((mut health, mut status)) = target_query.get_mut(ev.target) else {
+            continue;
+        };
+
+        if health.is_invulnerable() || health.is_dead() {
+            continue;
+        }
+
+        health.current = health.current.saturating_sub(ev.damage);
+        health.invulnerable = health.invulnerable_time;
+        status.knockback = Some(ev.knockback);
+        event_writer.send(ActorTickEvent {
+            actor: ev.target,
+            event: if health.is_dead() {
+                ActorEvent::Died
+            } else {
+                ActorEvent::Hit
+            },
+        });
+    }
+}
+
+fn heal_from_pickups(
+    mut pickup_events: EventReader<PickupEvent>,
+    mut health_query: Query<&mut Health>,
+) {
+    for ev in pickup_events.iter() {
+        if ev.pickup_type != PickupType::Health {
+            continue;
+        }
+
+        if let Ok(mut health) = health_query.get_mut(ev.collector_entity) {
+            health.current = (health.current + 1).min(health.max);
+        }
+    }
+}
+
+/// Blinks sprites while invulnerable, or just dims them with flashing effects turned off
+pub fn invulnerability_flicker(
+    settings: Res<Settings>,
+    mut sprite_query: Query<(&Health, &mut TextureAtlasSprite)>,
+) {
+    for (health, mut sprite) in &mut sprite_query {
+        let alpha = if !health.is_invulnerable() {
+            1.
+        } else if settings.accessibility.reduce_flashing {
+            0.6
+        } else if (health.invulnerable / FLICKER_PERIOD) as u32 % 2 == 0 {
+            1.
+        } else {
+            0.2
+        };
+
+        sprite.color.set_a(alpha);
+    }
+}
@@ -1,22 +1,52 @@
 mod actions;
+#[cfg(feature = "harness")]
+pub mod harness;
 mod loading;
 mod player;
 mod actor;
 mod camera;
+mod checkpoint;
+mod cuttable;
 mod door;
+mod enemy;
+mod fixed_step;
+mod health;
+mod melee;
+mod menu;
 mod pickup;
+mod progress;
+mod prop;
+mod replay;
+mod save;
+mod settings;
 mod sprite_anim;
+mod storage;
 mod ui_events;
 mod world;
+mod yell;
 
 use crate::actions::ActionsPlugin;
 use crate::camera::CameraPlugin;
+use crate::checkpoint::{CheckpointPlugin, CheckpointPresentationPlugin};
+use crate::cuttable::{CuttablePlugin, CuttablePresentationPlugin};
+use crate::door::{DoorPlugin, DoorPresentationPlugin};
+use crate::enemy::{EnemyPlugin, EnemyPresentationPlugin};
+use crate::fixed_step::FixedStepPlugin;
+use crate::health::{HealthPlugin, HealthPresentationPlugin};
 use crate::loading::LoadingPlugin;
-use crate::pickup::PickupPlugin;
-use crate::player::PlayerPlugin;
+use crate::melee::MeleePlugin;
+use crate::menu::MenuPlugin;
+use crate::pickup::{PickupPlugin, PickupPresentationPlugin};
+use crate::player::{PlayerPlugin, PlayerPresentationPlugin};
+use crate::progress::ProgressPlugin;
+use crate::prop::PropPlugin;
+use crate::replay::ReplayPlugin;
+use crate::save::SavePlugin;
+use crate::settings::SettingsPlugin;
 use crate::world::WorldPlugin;
+use crate::yell::YellPlugin;
 
-use actor::ActorPlugin;
+use actor::{ActorPlugin, ActorPresentationPlugin};
 use bevy::app::App;
 #[cfg(debug_assertions)]
 use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
@@ -25,6 +55,19 @@ use bevy_rapier2d::render::RapierDebugRenderPlugin;
 use sprite_anim::SpriteAnimationPlugin;
 use ui_events::UiEventPlugin;
 
+pub use actions::PlayerActions;
+pub use actor::{ActorEvent, ActorStatus, JumpState};
+pub use enemy::{Behaviour, EnemyType};
+pub use fixed_step::FIXED_TIMESTEP;
+pub use health::{Hazard, Health};
+pub use pickup::PickupType;
+pub use prop::PropType;
+pub use replay::{ReplayOptions, ReplayVerdict};
+pub use save::{LevelRecord, SaveData};
+pub use settings::Settings;
+pub use world::ChangeLevelEvent;
+pub use yell::YellEvent;
+
 // This example game uses States to separate logic
 // See https://bevy-cheatbook.github.io/programming/states.html
 // Or https://github.com/bevyengine/bevy/blob/main/examples/ecs/state.rs
@@ -43,22 +86,18 @@ enum GameState {
     LevelSelect,
     // Shows win screen, links back to main menu
     WinScreen,
+    // Window and game options, reached from the main or pause menu
+    Settings,
+    // Key and gamepad rebinding, reached from the settings menu
+    Controls,
 }
 
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_state::<GameState>()
-            .add_plugins(WorldPlugin)
-            .add_plugins(LoadingPlugin)
-            .add_plugins(UiEventPlugin)
-            .add_plugins(ActionsPlugin)
-            .add_plugins(PlayerPlugin)
-            .add_plugins(PickupPlugin)
-            .add_plugins(ActorPlugin)
-            .add_plugins(SpriteAnimationPlugin)
-            .add_plugins(CameraPlugin);
+        app.add_plugins(SimulationPlugin)
+            .add_plugins(PresentationPlugin);
 
         #[cfg(debug_assertions)]
         {
@@ -68,3 +107,52 @@ impl Plugin for GamePlugin {
         }
     }
 }
+
+/// Levels, physics and the rules of the game.
+/// Needs no window, renderer or audio, see `harness` for running it under `MinimalPlugins`.
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<GameState>()
+            .add_plugins(FixedStepPlugin)
+            .add_plugins(WorldPlugin)
+            .add_plugins(PlayerPlugin)
+            .add_plugins(PickupPlugin)
+            .add_plugins(DoorPlugin)
+            .add_plugins(HealthPlugin)
+            .add_plugins(CheckpointPlugin)
+            .add_plugins(PropPlugin)
+            .add_plugins(YellPlugin)
+            .add_plugins(MeleePlugin)
+            .add_plugins(CuttablePlugin)
+            .add_plugins(EnemyPlugin)
+            .add_plugins(ActorPlugin);
+    }
+}
+
+/// Everything around the simulation: devices, menus, saving, drawing and sound
+pub struct PresentationPlugin;
+
+impl Plugin for PresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(SettingsPlugin)
+            .add_plugins(LoadingPlugin)
+            .add_plugins(UiEventPlugin)
+            .add_plugins(MenuPlugin)
+            .add_plugins(ActionsPlugin)
+            .add_plugins(ReplayPlugin)
+            .add_plugins(ProgressPlugin)
+            .add_plugins(SavePlugin)
+            .add_plugins(ActorPresentationPlugin)
+            .add_plugins(PickupPresentationPlugin)
+            .add_plugins(DoorPresentationPlugin)
+            .add_plugins(HealthPresentationPlugin)
+            .add_plugins(PlayerPresentationPlugin)
+            .add_plugins(CheckpointPresentationPlugin)
+            .add_plugins(CuttablePresentationPlugin)
+            .add_plugins(EnemyPresentationPlugin)
+            .add_plugins(SpriteAnimationPlugin)
+            .add_plugins(CameraPlugin);
+    }
+}
@@ -1,6 +1,7 @@
 use crate::GameState;
 use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
 
 pub struct LoadingPlugin;
 
@@ -11,11 +12,12 @@ impl Plugin for LoadingPlugin {
     fn build(&self, app: &mut App) {
         app.add_loading_state(
             LoadingState::new(GameState::Loading)
-                .continue_to_state(GameState::Playing)
+                .continue_to_state(GameState::Menu)
         )
         .add_collection_to_loading_state::<_, FontAssets>(GameState::Loading)
         .add_collection_to_loading_state::<_, AudioAssets>(GameState::Loading)
         .add_collection_to_loading_state::<_, SpriteAssets>(GameState::Loading)
+        .add_collection_to_loading_state::<_, LevelAssets>(GameState::Loading)
         ;
     }
 }
@@ -51,6 +53,12 @@ pub struct AudioAssets {
     pub unlocked: Handle<AudioSource>,
 }
 
+#[derive(AssetCollection, Resource)]
+pub struct LevelAssets {
+    #[asset(path = "levels/World.ldtk")]
+    pub world: Handle<LdtkAsset>,
+}
+
 #[derive(AssetCollection, Resource)]
 pub struct SpriteAssets {
     #[asset(path = "sprites/bevy.png")]
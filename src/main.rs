@@ -1,24 +1,61 @@
 // disable console on windows for release builds
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use std::time::Duration;
+
 use bevy::{
+    app::ScheduleRunnerPlugin,
     prelude::*,
-    window::{PresentMode, WindowTheme},
+    render::{settings::WgpuSettings, RenderPlugin},
+    time::TimeUpdateStrategy,
+    window::{ExitCondition, WindowTheme},
+    winit::WinitPlugin,
     DefaultPlugins,
 };
-use platformer_game::GamePlugin;
+use platformer_game::{GamePlugin, ReplayOptions, ReplayVerdict, Settings, FIXED_TIMESTEP};
 
 fn main() {
-    App::new()
-        .insert_resource(Msaa::Sample4)
-        .insert_resource(ClearColor(Color::rgb(0.0, 0.0, 0.0)))
-        .add_plugins(
+    let replay = ReplayOptions::from_args(std::env::args().skip(1));
+
+    // Loaded up front so the window opens in the configured mode
+    let settings = Settings::load();
+
+    let mut app = App::new();
+    app.insert_resource(Msaa::Sample4)
+        .insert_resource(ClearColor(Color::rgb(0.0, 0.0, 0.0)));
+
+    if replay.as_ref().is_some_and(|replay| replay.verify) {
+        // Verifying a replay needs no window or GPU, and runs one simulation tick
+        // per update as fast as the machine allows
+        app.add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: None,
+                    exit_condition: ExitCondition::DontExit,
+                    close_when_requested: false,
+                })
+                .set(RenderPlugin {
+                    wgpu_settings: WgpuSettings {
+                        backends: None,
+                        ..default()
+                    },
+                })
+                .set(ImagePlugin::default_nearest())
+                .disable::<WinitPlugin>(),
+        )
+        .add_plugins(ScheduleRunnerPlugin::run_loop(Duration::ZERO))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            FIXED_TIMESTEP,
+        )));
+    } else {
+        app.add_plugins(
             DefaultPlugins
                 .set(WindowPlugin {
                     primary_window: Some(Window {
-                        resolution: (1200., 800.).into(),
+                        resolution: settings.resolution.into(),
                         title: "Platformer Game".to_string(),
-                        present_mode: PresentMode::AutoVsync,
+                        mode: settings.window_mode(),
+                        present_mode: settings.present_mode(),
                         // Tells wasm to resize the window according to the available canvas
                         fit_canvas_to_parent: true,
                         // Tells wasm not to override default event handling, like F5, Ctrl+R etc.
@@ -32,7 +69,20 @@ fn main() {
                 .set(AssetPlugin {
                     ..Default::default()
                 }),
-        )
-        .add_plugins(GamePlugin)
-        .run();
+        );
+    }
+
+    let verdict = ReplayVerdict::default();
+    if let Some(replay) = replay {
+        if replay.verify {
+            app.insert_resource(verdict.clone());
+        }
+        app.insert_resource(replay);
+    }
+
+    app.insert_resource(settings).add_plugins(GamePlugin).run();
+
+    if let Some(outcome) = verdict.get() {
+        std::process::exit(outcome.exit_code());
+    }
 }
@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    actor::{actor_movement, Actor, ActorEvent, ActorStatus, ActorTickEvent},
+    fixed_step::SimulationSet,
+    health::{DamageEvent, Damageable},
+    player::player_inputs,
+    GameState,
+};
+
+pub struct MeleePlugin;
+
+/// Lets an actor swing at whatever is in front of it when its `Actor::attack_input` is set
+#[derive(Component, Clone, Debug)]
+pub struct Attacker {
+    pub damage: u32,
+    /// Half size of the hitbox, which reaches out from the actor's center
+    pub reach: Vec2,
+    pub knockback: f32,
+    /// How long a swing lasts, another can't start before it ends
+    pub duration: f32,
+}
+
+impl Default for Attacker {
+    fn default() -> Self {
+        Attacker {
+            damage: 1,
+            reach: Vec2::new(8., 6.),
+            knockback: 120.,
+            duration: 0.3,
+        }
+    }
+}
+
+impl Plugin for MeleePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, actor_attack
+            .after(player_inputs)
+            .before(actor_movement)
+            .in_set(SimulationSet)
+            .run_if(in_state(GameState::Playing))
+        );
+    }
+}
+
+/// Hits every `Damageable` in a short box in front of the attacker, once at the start of a swing
+pub fn actor_attack(
+    fixed_time: Res<FixedTime>,
+    rapier_context: Res<RapierContext>,
+    mut damage_writer: EventWriter<DamageEvent>,
+    mut event_writer: EventWriter<ActorTickEvent>,
+    mut attacker_query: Query<(Entity, &Transform, &Actor, &Attacker, &mut ActorStatus)>,
+    damageable_query: Query<(), With<Damageable>>,
+) {
+    let dt = fixed_time.period.as_secs_f32();
+
+    for (entity, transform, actor, attacker, mut status) in &mut attacker_query {
+        if status.attacking > 0. {
+            status.attacking = (status.attacking - dt).max(0.);
+            continue;
+        }
+
+        if !actor.attack_input {
+            continue;
+        }
+
+        status.attacking = attacker.duration;
+        event_writer.send(ActorTickEvent {
+            actor: entity,
+            event: ActorEvent::Attack,
+        });
+
+        let forward = if status.facing_left { -1. } else { 1. };
+        let shape = Collider::cuboid(attacker.reach.x, attacker.reach.y);
+        let filter = QueryFilter::new().exclude_collider(entity);
+        let shape_pos = transform.translation.truncate() + Vec2::new(forward * attacker.reach.x, 0.);
+
+        rapier_context.intersections_with_shape(shape_pos, 0., &shape, filter, |target| -> bool {
+            if damageable_query.contains(target) {
+                damage_writer.send(DamageEvent {
+                    target,
+                    source: Some(entity),
+                    damage: attacker.damage,
+                    knockback: Vec2::new(forward * attacker.knockback, attacker.knockback * 0.5),
+                });
+            }
+            true
+        });
+    }
+}
@@ -0,0 +1,229 @@
+use bevy::prelude::*;
+
+use crate::actions::{set_menu_actions, Actions, Binding, InputAction, InputMap};
+use crate::loading::FontAssets;
+use crate::menu::{
+    despawn_menu, menu_mouse_input, menu_navigation, menu_root_style, menu_text,
+    spawn_menu_button, MenuBack, MenuButton, MenuRoot, MenuSelection,
+};
+use crate::ui_events::UiEvent;
+use crate::GameState;
+
+pub struct ControlsMenuPlugin;
+
+impl Plugin for ControlsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RebindCapture>()
+            .add_systems(OnEnter(GameState::Controls), spawn_controls_menu)
+            .add_systems(OnExit(GameState::Controls), (despawn_menu, stop_capture))
+            .add_systems(
+                Update,
+                (
+                    capture_binding
+                        .after(set_menu_actions)
+                        .before(menu_mouse_input),
+                    select_binding_slot
+                        .after(capture_binding)
+                        .before(menu_navigation),
+                    controls_events,
+                    update_binding_labels,
+                )
+                    .run_if(in_state(GameState::Controls)),
+            );
+    }
+}
+
+/// Player whose bindings are shown, the binding slot picked on the selected row,
+/// and the action waiting on its next key or button press
+#[derive(Resource, Default)]
+struct RebindCapture {
+    player: usize,
+    slot: usize,
+    action: Option<InputAction>,
+}
+
+/// The action on a menu row, the player row and the buttons after the actions have none
+fn row_action(index: usize) -> Option<InputAction> {
+    index.checked_sub(1).and_then(|index| InputAction::ALL.get(index).copied())
+}
+
+#[derive(Component, Clone, Copy)]
+enum ControlsLabel {
+    Player,
+    Action(InputAction),
+}
+
+fn spawn_controls_menu(mut commands: Commands, fonts: Res<FontAssets>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    row_gap: Val::Px(8.),
+                    ..menu_root_style()
+                },
+                ..default()
+            },
+            MenuRoot,
+            MenuBack(UiEvent::CloseControls),
+        ))
+        .with_children(|root| {
+            root.spawn(menu_text(&fonts, "Controls", 32.));
+
+            spawn_menu_button(root, &fonts, "", 0, UiEvent::NextControlsPlayer)
+                .insert(ControlsLabel::Player);
+
+            for (index, action) in InputAction::ALL.into_iter().enumerate() {
+                // Wider than other menus to fit a few bindings per row
+                spawn_menu_button(root, &fonts, "", index + 1, UiEvent::Rebind(action)).insert((
+                    ControlsLabel::Action(action),
+                    Style {
+                        width: Val::Px(720.),
+                        padding: UiRect::all(Val::Px(12.)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                ));
+            }
+
+            let index = InputAction::ALL.len() + 1;
+            spawn_menu_button(root, &fonts, "Reset Defaults", index, UiEvent::ResetControls);
+            spawn_menu_button(root, &fonts, "Back", index + 1, UiEvent::CloseControls);
+        });
+}
+
+fn stop_capture(mut capture: ResMut<RebindCapture>) {
+    capture.action = None;
+    capture.slot = 0;
+}
+
+fn controls_events(
+    mut ui_events: EventReader<UiEvent>,
+    mut capture: ResMut<RebindCapture>,
+    mut input_map: ResMut<InputMap>,
+) {
+    for event in ui_events.iter() {
+        match event {
+            UiEvent::NextControlsPlayer => {
+                capture.player = (capture.player + 1) % input_map.players.len().max(1);
+            }
+            UiEvent::Rebind(action) => capture.action = Some(*action),
+            UiEvent::ResetControls => *input_map = InputMap::default(),
+            _ => (),
+        }
+    }
+}
+
+/// Left and right pick which of the selected action's bindings gets replaced,
+/// one past the last binding adds a new one
+fn select_binding_slot(
+    actions: Res<Actions>,
+    selection: Res<MenuSelection>,
+    input_map: Res<InputMap>,
+    mut capture: ResMut<RebindCapture>,
+) {
+    if capture.action.is_some() {
+        return;
+    }
+
+    if actions.menu_movement.y != 0 {
+        capture.slot = 0;
+        return;
+    }
+
+    let Some(action) = row_action(selection.selected) else {
+        return;
+    };
+
+    let slots = input_map.bindings(capture.player, action).len() as i32 + 1;
+    let slot = capture.slot.min(slots as usize - 1) as i32 + actions.menu_movement.x;
+    capture.slot = slot.rem_euclid(slots) as usize;
+}
+
+/// Binds the next key or button pressed to the action being captured, Escape or Start cancel.
+/// Runs ahead of the menu systems and swallows their input, so the press only rebinds.
+fn capture_binding(
+    mut capture: ResMut<RebindCapture>,
+    mut input_map: ResMut<InputMap>,
+    mut actions: ResMut<Actions>,
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+) {
+    let Some(action) = capture.action else {
+        return;
+    };
+
+    actions.menu_movement = IVec2::ZERO;
+    actions.confirm = false;
+    actions.back = false;
+
+    let cancelled = keyboard_input.just_pressed(KeyCode::Escape)
+        || gamepad_buttons
+            .get_just_pressed()
+            .any(|button| button.button_type == GamepadButtonType::Start);
+    if cancelled {
+        capture.action = None;
+        return;
+    }
+
+    let binding = keyboard_input
+        .get_just_pressed()
+        .next()
+        .map(|key| Binding::Key(*key))
+        .or_else(|| {
+            gamepad_buttons
+                .get_just_pressed()
+                .next()
+                .map(|button| Binding::Button(button.button_type))
+        });
+
+    if let Some(binding) = binding {
+        input_map.rebind(capture.player, action, capture.slot, binding);
+        capture.action = None;
+    }
+}
+
+fn update_binding_labels(
+    capture: Res<RebindCapture>,
+    input_map: Res<InputMap>,
+    selection: Res<MenuSelection>,
+    label_query: Query<(&ControlsLabel, &MenuButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (label, button, children) in &label_query {
+        let text = match label {
+            ControlsLabel::Player => format!("Player: {}", capture.player + 1),
+            ControlsLabel::Action(action) if capture.action == Some(*action) => {
+                format!("{}: press a key, Esc to cancel", action.name())
+            }
+            ControlsLabel::Action(action) => {
+                let mut bindings: Vec<String> = input_map
+                    .bindings(capture.player, *action)
+                    .iter()
+                    .map(Binding::name)
+                    .collect();
+
+                // Brackets mark the slot a rebind will replace on the selected row
+                if button.index == selection.selected {
+                    let slot = capture.slot.min(bindings.len());
+                    if slot == bindings.len() {
+                        bindings.push(String::from("+"));
+                    }
+                    bindings[slot] = format!("[{}]", bindings[slot]);
+                }
+
+                if bindings.is_empty() {
+                    format!("{}: none", action.name())
+                } else {
+                    format!("{}: {}", action.name(), bindings.join(", "))
+                }
+            }
+        };
+
+        for child in children.iter() {
+            if let Ok(mut child_text) = text_query.get_mut(*child) {
+                child_text.sections[0].value = text.clone();
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::loading::{FontAssets, LevelAssets};
+use crate::menu::{
+    despawn_menu, menu_root_style, menu_text, spawn_menu_button, DisabledButton, MenuBack,
+    MenuRoot, MenuSelection,
+};
+use crate::progress::LevelProgress;
+use crate::ui_events::UiEvent;
+use crate::GameState;
+
+pub struct LevelSelectPlugin;
+
+const LEVEL_COLUMNS: usize = 4;
+
+impl Plugin for LevelSelectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::LevelSelect), spawn_level_select)
+            .add_systems(OnExit(GameState::LevelSelect), despawn_menu);
+    }
+}
+
+fn spawn_level_select(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    level_assets: Res<LevelAssets>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    progress: Res<LevelProgress>,
+    mut selection: ResMut<MenuSelection>,
+) {
+    let level_names: Vec<String> = ldtk_assets
+        .get(&level_assets.world)
+        .map(|ldtk| {
+            ldtk.project
+                .levels
+                .iter()
+                .map(|level| level.identifier.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    selection.columns = LEVEL_COLUMNS;
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: menu_root_style(),
+                ..default()
+            },
+            MenuRoot,
+            MenuBack(UiEvent::OpenMainMenu),
+        ))
+        .with_children(|root| {
+            root.spawn(menu_text(&fonts, "Level Select", 32.));
+
+            root.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(LEVEL_COLUMNS as f32 * 220.),
+                    flex_direction: FlexDirection::Row,
+                    flex_wrap: FlexWrap::Wrap,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(12.),
+                    column_gap: Val::Px(12.),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|grid| {
+                for (index, name) in level_names.iter().enumerate() {
+                    let status = if progress.is_completed(index) {
+                        "Completed"
+                    } else if progress.is_unlocked(index) {
+                        "Unlocked"
+                    } else {
+                        "Locked"
+                    };
+
+                    let mut button = spawn_menu_button(
+                        grid,
+                        &fonts,
+                        &format!("{}\n{}", name, status),
+                        index,
+                        UiEvent::PlayLevel(index),
+                    );
+
+                    button.insert(Style {
+                        width: Val::Px(200.),
+                        padding: UiRect::all(Val::Px(12.)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    });
+
+                    if !progress.is_unlocked(index) {
+                        button.insert(DisabledButton);
+                    }
+                }
+            });
+
+            spawn_menu_button(
+                root,
+                &fonts,
+                "Back",
+                level_names.len(),
+                UiEvent::OpenMainMenu,
+            );
+        });
+}
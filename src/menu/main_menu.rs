@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+
+use crate::loading::{FontAssets, SpriteAssets};
+use crate::menu::{despawn_menu, menu_root_style, spawn_menu_button, MenuRoot};
+use crate::save::SaveSlot;
+use crate::ui_events::UiEvent;
+use crate::GameState;
+
+pub struct MainMenuPlugin;
+
+impl Plugin for MainMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Menu), spawn_main_menu)
+            .add_systems(OnExit(GameState::Menu), despawn_menu)
+            .add_systems(Update, update_save_slot_label.run_if(in_state(GameState::Menu)));
+    }
+}
+
+fn spawn_main_menu(mut commands: Commands, fonts: Res<FontAssets>, sprites: Res<SpriteAssets>) {
+    let mut buttons = vec![
+        ("New Game", UiEvent::NewGame),
+        ("Continue", UiEvent::LoadGame),
+        ("Save Slot", UiEvent::NextSaveSlot),
+        ("Level Select", UiEvent::OpenLevelSelect),
+        ("Settings", UiEvent::OpenSettings),
+    ];
+
+    // There is nothing to quit to in the browser
+    #[cfg(not(target_arch = "wasm32"))]
+    buttons.push(("Quit", UiEvent::QuitGame));
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: menu_root_style(),
+                ..default()
+            },
+            MenuRoot,
+        ))
+        .with_children(|root| {
+            root.spawn(ImageBundle {
+                style: Style {
+                    width: Val::Px(512.),
+                    height: Val::Px(256.),
+                    margin: UiRect::bottom(Val::Px(24.)),
+                    ..default()
+                },
+                image: UiImage::new(sprites.texture_title.clone()),
+                ..default()
+            });
+
+            for (index, (label, event)) in buttons.into_iter().enumerate() {
+                let is_slot_button = matches!(event, UiEvent::NextSaveSlot);
+                let mut button = spawn_menu_button(root, &fonts, label, index, event);
+
+                if is_slot_button {
+                    button.insert(SaveSlotButton);
+                }
+            }
+        });
+}
+
+#[derive(Component)]
+struct SaveSlotButton;
+
+fn update_save_slot_label(
+    slot: Res<SaveSlot>,
+    button_query: Query<&Children, With<SaveSlotButton>>,
+    mut text_query: Query<&mut Text>,
+) {
+    for children in &button_query {
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                text.sections[0].value = format!("Save Slot: {}", slot.index + 1);
+            }
+        }
+    }
+}
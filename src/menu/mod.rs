@@ -0,0 +1,240 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+
+use crate::actions::{set_menu_actions, Actions};
+use crate::loading::FontAssets;
+use crate::ui_events::UiEvent;
+
+use self::controls_menu::ControlsMenuPlugin;
+use self::level_select::LevelSelectPlugin;
+use self::main_menu::MainMenuPlugin;
+use self::pause_menu::PauseMenuPlugin;
+use self::settings_menu::SettingsMenuPlugin;
+use self::win_screen::WinScreenPlugin;
+
+mod controls_menu;
+mod level_select;
+mod main_menu;
+mod pause_menu;
+mod settings_menu;
+mod win_screen;
+
+pub struct MenuPlugin;
+
+const BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+const SELECTED_BUTTON_COLOR: Color = Color::rgb(0.35, 0.35, 0.55);
+const DISABLED_BUTTON_COLOR: Color = Color::rgb(0.08, 0.08, 0.08);
+const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+
+/// Shared navigation for every menu screen, driven by `Actions` so that
+/// keyboard, gamepad and mouse can all be used to pick a button
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuSelection>()
+            .add_plugins(MainMenuPlugin)
+            .add_plugins(PauseMenuPlugin)
+            .add_plugins(LevelSelectPlugin)
+            .add_plugins(SettingsMenuPlugin)
+            .add_plugins(ControlsMenuPlugin)
+            .add_plugins(WinScreenPlugin)
+            .add_systems(
+                Update,
+                (
+                    menu_mouse_input,
+                    menu_navigation,
+                    menu_button_colors,
+                    menu_confirm,
+                    menu_back,
+                )
+                    .chain()
+                    .after(set_menu_actions),
+            );
+    }
+}
+
+/// Root node of a menu screen, despawned along with its children when the screen closes
+#[derive(Component, Default, Clone)]
+pub struct MenuRoot;
+
+/// Event sent when the back action is pressed while this menu screen is open
+#[derive(Component, Clone)]
+pub struct MenuBack(pub UiEvent);
+
+#[derive(Component, Clone)]
+pub struct MenuButton {
+    pub index: usize,
+    pub event: UiEvent,
+}
+
+/// Buttons that can still be selected but do nothing when confirmed
+#[derive(Component, Default, Clone)]
+pub struct DisabledButton;
+
+#[derive(Resource, Debug)]
+pub struct MenuSelection {
+    pub selected: usize,
+    pub columns: usize,
+}
+
+impl Default for MenuSelection {
+    fn default() -> Self {
+        MenuSelection {
+            selected: 0,
+            columns: 1,
+        }
+    }
+}
+
+pub fn menu_root_style() -> Style {
+    Style {
+        width: Val::Percent(100.),
+        height: Val::Percent(100.),
+        flex_direction: FlexDirection::Column,
+        align_items: AlignItems::Center,
+        justify_content: JustifyContent::Center,
+        row_gap: Val::Px(12.),
+        ..default()
+    }
+}
+
+pub fn menu_text(fonts: &FontAssets, text: &str, font_size: f32) -> TextBundle {
+    TextBundle::from_section(
+        text,
+        TextStyle {
+            font: fonts.press_start.clone(),
+            font_size,
+            color: TEXT_COLOR,
+        },
+    )
+}
+
+pub fn spawn_menu_button<'w, 's, 'a>(
+    parent: &'a mut ChildBuilder<'w, 's, '_>,
+    fonts: &FontAssets,
+    label: &str,
+    index: usize,
+    event: UiEvent,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut button = parent.spawn((
+        ButtonBundle {
+            style: Style {
+                width: Val::Px(360.),
+                padding: UiRect::all(Val::Px(12.)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: BUTTON_COLOR.into(),
+            ..default()
+        },
+        MenuButton { index, event },
+    ));
+
+    button.with_children(|button| {
+        button.spawn(menu_text(fonts, label, 20.));
+    });
+
+    button
+}
+
+pub fn despawn_menu(
+    mut commands: Commands,
+    menu_query: Query<Entity, With<MenuRoot>>,
+    mut selection: ResMut<MenuSelection>,
+) {
+    for entity in &menu_query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    *selection = MenuSelection::default();
+}
+
+fn menu_mouse_input(
+    interaction_query: Query<
+        (&Interaction, &MenuButton, Option<&DisabledButton>),
+        Changed<Interaction>,
+    >,
+    mut selection: ResMut<MenuSelection>,
+    mut ui_events: EventWriter<UiEvent>,
+) {
+    for (interaction, button, disabled) in &interaction_query {
+        match interaction {
+            Interaction::Hovered => selection.selected = button.index,
+            Interaction::Pressed => {
+                selection.selected = button.index;
+                if disabled.is_none() {
+                    ui_events.send(button.event.clone());
+                }
+            }
+            Interaction::None => (),
+        }
+    }
+}
+
+fn menu_navigation(
+    actions: Res<Actions>,
+    mut selection: ResMut<MenuSelection>,
+    button_query: Query<&MenuButton>,
+) {
+    let count = button_query.iter().count() as i32;
+    if count == 0 || actions.menu_movement == IVec2::ZERO {
+        return;
+    }
+
+    let columns = selection.columns.max(1) as i32;
+    let mut selected = selection.selected as i32;
+
+    if columns > 1 {
+        selected += actions.menu_movement.x;
+    }
+    selected -= actions.menu_movement.y * columns;
+
+    selection.selected = selected.rem_euclid(count) as usize;
+}
+
+fn menu_button_colors(
+    selection: Res<MenuSelection>,
+    mut button_query: Query<(&MenuButton, &mut BackgroundColor, Option<&DisabledButton>)>,
+) {
+    for (button, mut color, disabled) in &mut button_query {
+        *color = if button.index == selection.selected {
+            SELECTED_BUTTON_COLOR.into()
+        } else if disabled.is_some() {
+            DISABLED_BUTTON_COLOR.into()
+        } else {
+            BUTTON_COLOR.into()
+        };
+    }
+}
+
+fn menu_confirm(
+    actions: Res<Actions>,
+    selection: Res<MenuSelection>,
+    button_query: Query<&MenuButton, Without<DisabledButton>>,
+    mut ui_events: EventWriter<UiEvent>,
+) {
+    if !actions.confirm {
+        return;
+    }
+
+    if let Some(button) = button_query
+        .iter()
+        .find(|button| button.index == selection.selected)
+    {
+        ui_events.send(button.event.clone());
+    }
+}
+
+fn menu_back(
+    actions: Res<Actions>,
+    back_query: Query<&MenuBack, With<MenuRoot>>,
+    mut ui_events: EventWriter<UiEvent>,
+) {
+    if !actions.back {
+        return;
+    }
+
+    for MenuBack(event) in &back_query {
+        ui_events.send(event.clone());
+    }
+}
@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+
+use crate::actions::{set_pause_actions, Actions};
+use crate::loading::{FontAssets, SpriteAssets};
+use crate::menu::{despawn_menu, menu_root_style, spawn_menu_button, MenuBack, MenuRoot};
+use crate::ui_events::UiEvent;
+use crate::GameState;
+
+pub struct PauseMenuPlugin;
+
+/// The pause overlay is drawn on top of the still loaded level,
+/// gameplay systems are all gated on `GameState::Playing` so nothing moves underneath
+impl Plugin for PauseMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Paused), spawn_pause_menu)
+            .add_systems(OnExit(GameState::Paused), despawn_menu)
+            .add_systems(
+                Update,
+                (
+                    pause_game.run_if(in_state(GameState::Playing)),
+                    unpause_game.run_if(in_state(GameState::Paused)),
+                )
+                    .after(set_pause_actions),
+            );
+    }
+}
+
+fn spawn_pause_menu(mut commands: Commands, fonts: Res<FontAssets>, sprites: Res<SpriteAssets>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: menu_root_style(),
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                ..default()
+            },
+            MenuRoot,
+            MenuBack(UiEvent::Resume),
+        ))
+        .with_children(|root| {
+            root.spawn(ImageBundle {
+                style: Style {
+                    width: Val::Px(256.),
+                    height: Val::Px(256.),
+                    ..default()
+                },
+                image: UiImage::new(sprites.texture_pause_background.clone()),
+                ..default()
+            });
+
+            spawn_menu_button(root, &fonts, "Resume", 0, UiEvent::Resume);
+            spawn_menu_button(root, &fonts, "Restart Level", 1, UiEvent::RestartLevel);
+            spawn_menu_button(root, &fonts, "Settings", 2, UiEvent::OpenSettings);
+            spawn_menu_button(root, &fonts, "Quit to Menu", 3, UiEvent::OpenMainMenu);
+        });
+}
+
+fn pause_game(actions: Res<Actions>, mut next_state: ResMut<NextState<GameState>>) {
+    if actions.pause {
+        next_state.set(GameState::Paused);
+    }
+}
+
+fn unpause_game(actions: Res<Actions>, mut ui_events: EventWriter<UiEvent>) {
+    if actions.pause {
+        ui_events.send(UiEvent::Resume);
+    }
+}
@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+
+use crate::loading::FontAssets;
+use crate::menu::{despawn_menu, menu_root_style, menu_text, spawn_menu_button, MenuBack, MenuRoot};
+use crate::settings::Settings;
+use crate::ui_events::UiEvent;
+use crate::GameState;
+
+pub struct SettingsMenuPlugin;
+
+impl Plugin for SettingsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Settings), spawn_settings_menu)
+            .add_systems(OnExit(GameState::Settings), despawn_menu)
+            .add_systems(
+                Update,
+                update_setting_labels.run_if(in_state(GameState::Settings)),
+            );
+    }
+}
+
+/// Which setting a settings button shows and changes
+#[derive(Component, Clone, Copy)]
+enum SettingToggle {
+    Fullscreen,
+    Vsync,
+    MasterVolume,
+    EffectsVolume,
+    ReduceFlashing,
+    UiScale,
+}
+
+fn spawn_settings_menu(mut commands: Commands, fonts: Res<FontAssets>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: menu_root_style(),
+                ..default()
+            },
+            MenuRoot,
+            MenuBack(UiEvent::CloseSettings),
+        ))
+        .with_children(|root| {
+            root.spawn(menu_text(&fonts, "Settings", 32.));
+
+            let toggles = [
+                (SettingToggle::Fullscreen, UiEvent::ToggleFullscreen),
+                (SettingToggle::Vsync, UiEvent::ToggleVsync),
+                (SettingToggle::MasterVolume, UiEvent::CycleMasterVolume),
+                (SettingToggle::EffectsVolume, UiEvent::CycleEffectsVolume),
+                (SettingToggle::ReduceFlashing, UiEvent::ToggleReduceFlashing),
+                (SettingToggle::UiScale, UiEvent::CycleUiScale),
+            ];
+            let toggle_count = toggles.len();
+
+            for (index, (toggle, event)) in toggles.into_iter().enumerate() {
+                // Real label is filled in by update_setting_labels
+                spawn_menu_button(root, &fonts, "", index, event).insert(toggle);
+            }
+
+            spawn_menu_button(root, &fonts, "Controls", toggle_count, UiEvent::OpenControls);
+            spawn_menu_button(root, &fonts, "Back", toggle_count + 1, UiEvent::CloseSettings);
+        });
+}
+
+fn update_setting_labels(
+    settings: Res<Settings>,
+    toggle_query: Query<(&SettingToggle, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (toggle, children) in &toggle_query {
+        let label = match toggle {
+            SettingToggle::Fullscreen => format!("Fullscreen: {}", on_off(settings.fullscreen)),
+            SettingToggle::Vsync => format!("VSync: {}", on_off(settings.vsync)),
+            SettingToggle::MasterVolume => {
+                format!("Volume: {}%", percent(settings.audio.master_volume))
+            }
+            SettingToggle::EffectsVolume => {
+                format!("Effects: {}%", percent(settings.audio.effects_volume))
+            }
+            // Kept short to fit the button, flashing effects are on unless reduced
+            SettingToggle::ReduceFlashing => format!(
+                "Flash FX: {}",
+                on_off(!settings.accessibility.reduce_flashing)
+            ),
+            SettingToggle::UiScale => {
+                format!("UI Scale: {}%", percent(settings.accessibility.ui_scale))
+            }
+        };
+
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                text.sections[0].value = label.clone();
+            }
+        }
+    }
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+fn percent(value: f32) -> i32 {
+    (value * 100.).round() as i32
+}
@@ -0,0 +1,85 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::loading::{FontAssets, LevelAssets, SpriteAssets};
+use crate::menu::{despawn_menu, menu_root_style, menu_text, spawn_menu_button, MenuBack, MenuRoot};
+use crate::progress::{format_time, RunStats};
+use crate::ui_events::UiEvent;
+use crate::GameState;
+
+pub struct WinScreenPlugin;
+
+impl Plugin for WinScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::WinScreen), spawn_win_screen)
+            .add_systems(OnExit(GameState::WinScreen), despawn_menu);
+    }
+}
+
+fn spawn_win_screen(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    sprites: Res<SpriteAssets>,
+    level_assets: Res<LevelAssets>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    stats: Res<RunStats>,
+) {
+    let ldtk = ldtk_assets.get(&level_assets.world);
+    let level_name = |index: usize| -> String {
+        ldtk.and_then(|ldtk| ldtk.project.levels.get(index))
+            .map(|level| level.identifier.clone())
+            .unwrap_or_else(|| format!("Level {}", index))
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: menu_root_style(),
+                ..default()
+            },
+            MenuRoot,
+            MenuBack(UiEvent::OpenMainMenu),
+        ))
+        .with_children(|root| {
+            root.spawn(ImageBundle {
+                style: Style {
+                    width: Val::Px(384.),
+                    height: Val::Px(192.),
+                    ..default()
+                },
+                image: UiImage::new(sprites.texture_victory.clone()),
+                ..default()
+            });
+
+            root.spawn(menu_text(
+                &fonts,
+                &format!("Time: {}", format_time(stats.total_time)),
+                20.,
+            ));
+            root.spawn(menu_text(&fonts, &format!("Deaths: {}", stats.deaths), 20.));
+            root.spawn(menu_text(&fonts, &format!("Souls: {}", stats.souls), 20.));
+
+            root.spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    margin: UiRect::vertical(Val::Px(12.)),
+                    row_gap: Val::Px(6.),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|level_list| {
+                for (index, time) in stats.level_times.iter() {
+                    level_list.spawn(menu_text(
+                        &fonts,
+                        &format!("{}  {}", level_name(*index), format_time(*time)),
+                        14.,
+                    ));
+                }
+            });
+
+            spawn_menu_button(root, &fonts, "Main Menu", 0, UiEvent::OpenMainMenu);
+            spawn_menu_button(root, &fonts, "New Game", 1, UiEvent::NewGame);
+        });
+}
@@ -1,16 +1,26 @@
 use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 
+use crate::{
+    fixed_step::SimulationSet, player::Dying, settings::Settings, world::Labeled, GameState,
+};
+
 pub struct PickupPlugin;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum PickupType {
-    _XP,
+    Soul,
+    Coin,
+    Health,
+    Key,
 }
 
 #[derive(Component, Clone, Default)]
 pub struct Pickup {
     pub pickup_type: Option<PickupType>,
+    pub sound: Handle<AudioSource>,
 }
 
 #[derive(Component, Default, Clone)]
@@ -21,24 +31,149 @@ pub struct PickupEvent {
     pub pickup_entity: Entity,
     pub pickup_type: PickupType,
     pub collector_entity: Entity,
+    pub sound: Handle<AudioSource>,
+}
+
+#[derive(Clone, Default, Bundle)]
+pub struct PickupBundle {
+    pub sprite_bundle: SpriteBundle,
+    pub collider: Collider,
+    pub sensor: Sensor,
+    pub label: Labeled,
+    pub pickup: Pickup,
+}
+
+impl PickupType {
+    pub fn from_identifier(identifier: &str) -> Option<PickupType> {
+        match identifier {
+            "Soul" => Some(PickupType::Soul),
+            "Coin" => Some(PickupType::Coin),
+            "Health" => Some(PickupType::Health),
+            "Key" => Some(PickupType::Key),
+            _ => None,
+        }
+    }
+
+    pub fn default_sprite_path(&self) -> &'static str {
+        match self {
+            PickupType::Soul => "sprites/soul.png",
+            PickupType::Coin => "sprites/coin.png",
+            PickupType::Health => "sprites/health.png",
+            PickupType::Key => "sprites/key.png",
+        }
+    }
+
+    pub fn default_sound_path(&self) -> &'static str {
+        match self {
+            PickupType::Soul => "audio/soul_pickup.wav",
+            PickupType::Coin => "audio/pickup1.ogg",
+            PickupType::Health => "audio/pickup1.ogg",
+            PickupType::Key => "audio/unlocked2.wav",
+        }
+    }
+
+    fn collider(&self) -> Collider {
+        match self {
+            PickupType::Soul => Collider::capsule_y(2., 5.),
+            PickupType::Coin => Collider::ball(5.),
+            PickupType::Health => Collider::ball(6.),
+            PickupType::Key => Collider::cuboid(8., 4.),
+        }
+    }
+}
+
+impl LdtkEntity for PickupBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        _layer_instance: &LayerInstance,
+        _tileset: Option<&Handle<Image>>,
+        _tileset_definition: Option<&TilesetDefinition>,
+        asset_server: &AssetServer,
+        _texture_atlases: &mut Assets<TextureAtlas>,
+    ) -> Self {
+        let pickup_type = PickupType::from_identifier(&entity_instance.identifier)
+            .expect("Pickup bundle registered for an unknown LDtk entity");
+
+        let mut sprite_path: String = pickup_type.default_sprite_path().into();
+        let mut sound_path: String = pickup_type.default_sound_path().into();
+
+        for field in entity_instance.field_instances.iter() {
+            match field.identifier.as_str() {
+                "SpritePath" => {
+                    if let FieldValue::String(Some(value)) = &field.value {
+                        sprite_path = value.clone();
+                    }
+                }
+                "SoundPath" => {
+                    if let FieldValue::String(Some(value)) = &field.value {
+                        sound_path = value.clone();
+                    }
+                }
+                unknown => println!("Unknown field \"{}\" on LDtk pickup object!", unknown),
+            }
+        }
+
+        PickupBundle::new(pickup_type, &sprite_path, &sound_path, asset_server)
+    }
+}
+
+impl PickupBundle {
+    pub fn new(
+        pickup_type: PickupType,
+        sprite_path: &str,
+        sound_path: &str,
+        asset_server: &AssetServer,
+    ) -> Self {
+        PickupBundle {
+            sprite_bundle: SpriteBundle {
+                texture: asset_server.load(sprite_path),
+                transform: Transform::from_translation(Vec3::new(0., 0., 0.75)),
+                ..Default::default()
+            },
+            collider: pickup_type.collider(),
+            sensor: Sensor,
+            label: Labeled {
+                name: format!("{:?}", pickup_type).to_lowercase(),
+            },
+            pickup: Pickup {
+                pickup_type: Some(pickup_type),
+                sound: asset_server.load(sound_path),
+            },
+        }
+    }
 }
 
 impl Plugin for PickupPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_event::<PickupEvent>()
-            .add_systems(Update, check_for_pickups)
+            .add_systems(FixedUpdate, check_for_pickups
+                .in_set(SimulationSet)
+                .run_if(in_state(GameState::Playing))
+            )
         ;
     }
 }
 
+pub struct PickupPresentationPlugin;
+
+impl Plugin for PickupPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, play_pickup_sounds.run_if(in_state(GameState::Playing)));
+    }
+}
+
 pub fn check_for_pickups(
     mut pickup_writer: EventWriter<PickupEvent>,
     rapier_context: Res<RapierContext>,
-    collector_query: Query<(Entity, &Transform), With<PickupCollector>>,
+    collector_query: Query<(Entity, &Transform), (With<PickupCollector>, Without<Dying>)>,
     pickup_query: Query<&Pickup>,
     mut commands: Commands,
 ) {
+    // Despawning waits for the end of the tick, so a pickup touched by two collectors
+    // would otherwise be collected twice
+    let mut collected = HashSet::new();
+
     for (collector_entity, transform) in collector_query.iter() {
         let shape = Collider::capsule_y(5.5, 5.5);
         let filter = QueryFilter::new();
@@ -46,11 +181,16 @@ pub fn check_for_pickups(
 
         rapier_context.intersections_with_shape(shape_pos, 0., &shape, filter, |entity| -> bool {
             if let Ok(pickup) = pickup_query.get(entity) {
+                if !collected.insert(entity) {
+                    return true;
+                }
+
                 if let Some(pickup_type) = &pickup.pickup_type {
                     pickup_writer.send(PickupEvent {
                         pickup_entity: entity,
                         pickup_type: pickup_type.clone(),
                         collector_entity,
+                        sound: pickup.sound.clone(),
                     });
                 }
 
@@ -61,3 +201,16 @@ pub fn check_for_pickups(
         });
     }
 }
+
+fn play_pickup_sounds(
+    mut pickup_events: EventReader<PickupEvent>,
+    settings: Res<Settings>,
+    mut commands: Commands,
+) {
+    for ev in pickup_events.iter() {
+        commands.spawn(AudioSourceBundle {
+            settings: settings.effect_playback(),
+            source: ev.sound.clone(),
+        });
+    }
+}
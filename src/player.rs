@@ -1,8 +1,14 @@
-use crate::actions::Actions;
+use crate::actions::{Actions, LocalPlayers};
 use crate::actor::*;
-use crate::door::Door;
+use crate::checkpoint::ActiveCheckpoint;
+use crate::door::{Door, LevelSouls};
+use crate::fixed_step::{RenderInterpolation, SimulationSet};
+use crate::health::{apply_damage, invulnerability_flicker, Health};
+use crate::melee::Attacker;
 use crate::sprite_anim::SpriteAnimator;
-use crate::world::{ChangeLevelEvent, Labeled, ReloadWorldEvent};
+use crate::world::{ChangeLevelEvent, Labeled};
+use crate::yell::Yeller;
+use crate::GameState;
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
@@ -11,26 +17,89 @@ pub struct PlayerPlugin;
 
 #[derive(Component, Default, Clone)]
 pub struct Player {
-    index: usize,
+    pub index: usize,
 }
 
-#[derive(Component, Debug, Default, Clone)]
-pub struct TouchDeath;
+/// Sprites handed out to players that join without an LDtk `Player` of their own
+const PLAYER_SPRITES: [&str; 2] = ["sprites/baby_jake.png", "sprites/baby_jake_2.png"];
+
+/// How long a dead player stays down before coming back
+const DEATH_TIME: f32 = 0.8;
+
+#[derive(Event)]
+pub struct PlayerDeathEvent {
+    pub player: Entity,
+}
+
+/// A player spawned for a joined gamepad rather than placed by the level,
+/// removed again when that gamepad leaves
+#[derive(Component, Default, Clone)]
+pub struct JoinedPlayer;
+
+/// Where the player first appeared in the level, used when there is no active checkpoint
+#[derive(Component, Clone, Debug)]
+pub struct SpawnPoint(pub Vec3);
+
+/// Out of health and playing the death animation, respawns once the timer runs out
+#[derive(Component, Clone, Debug)]
+pub struct Dying {
+    pub timer: f32,
+}
 
 /// This plugin handles player related stuff like movement
 /// Player logic is only active during the State `GameState::Playing`
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, player_inputs
+        // Filled in by the `ActionsPlugin` in game, or directly by a test harness
+        app.init_resource::<Actions>()
+        .init_resource::<LocalPlayers>()
+        .add_event::<PlayerDeathEvent>()
+        .add_systems(FixedUpdate, player_inputs
             .after(actor_status)
-            .after(crate::actions::set_movement_actions)
             .before(actor_movement)
+            .in_set(SimulationSet)
+            .run_if(in_state(GameState::Playing))
+        )
+        .add_systems(FixedUpdate, player_win
+            .after(actor_movement)
+            .in_set(SimulationSet)
+            .run_if(in_state(GameState::Playing))
+        )
+        .add_systems(FixedUpdate, record_spawn_points
+            .before(actor_status)
+            .in_set(SimulationSet)
+            .run_if(in_state(GameState::Playing))
+        )
+        .add_systems(FixedUpdate, player_death
+            .after(apply_damage)
+            .in_set(SimulationSet)
+            .run_if(in_state(GameState::Playing))
+        )
+        .add_systems(FixedUpdate, player_respawn
+            .before(actor_status)
+            .after(record_spawn_points)
+            .in_set(SimulationSet)
+            .run_if(in_state(GameState::Playing))
+        )
+        .add_systems(Update, sync_local_players
+            .after(crate::actions::set_movement_actions)
+            .run_if(in_state(GameState::Playing))
         )
-        .add_systems(Update, (player_death, player_win))
         ;
     }
 }
 
+pub struct PlayerPresentationPlugin;
+
+impl Plugin for PlayerPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, death_animation
+            .after(invulnerability_flicker)
+            .run_if(in_state(GameState::Playing))
+        );
+    }
+}
+
 #[derive(Clone, Default, Bundle)]
 pub struct PlayerBundle {
     pub sprite_sheet_bundle: SpriteSheetBundle,
@@ -50,6 +119,10 @@ pub struct PlayerBundle {
     pub squashy: Squashy,
     pub carrier: Carrier,
     pub carryable: Carryable,
+    pub render_interpolation: RenderInterpolation,
+    pub health: Health,
+    pub yeller: Yeller,
+    pub attacker: Attacker,
 }
 
 impl LdtkEntity for PlayerBundle {
@@ -67,6 +140,7 @@ impl LdtkEntity for PlayerBundle {
         let mut index = 1;
         let mut sprite_path: String = "sprites/baby_jake.png".into();
         let mut actor = Actor::default();
+        let mut health = Health::default();
 
         for field in entity_instance.field_instances.iter() {
             match field.identifier.as_str() {
@@ -110,6 +184,56 @@ impl LdtkEntity for PlayerBundle {
                         actor.jump_time = value;
                     }
                 }
+                "WallSlideSpeed" => {
+                    if let FieldValue::Float(Some(value)) = field.value {
+                        actor.wall_slide_speed = value;
+                    }
+                }
+                "WallJumpPower" => {
+                    if let FieldValue::Float(Some(value)) = field.value {
+                        actor.wall_jump_speed = value;
+                    }
+                }
+                "WallJumpKick" => {
+                    if let FieldValue::Float(Some(value)) = field.value {
+                        actor.wall_jump_kick = value;
+                    }
+                }
+                "WallJumpLockTime" => {
+                    if let FieldValue::Float(Some(value)) = field.value {
+                        actor.wall_jump_lock_time = value;
+                    }
+                }
+                "JumpCut" => {
+                    if let FieldValue::Float(Some(value)) = field.value {
+                        actor.jump_cut = value;
+                    }
+                }
+                "CoyoteTime" => {
+                    if let FieldValue::Float(Some(value)) = field.value {
+                        actor.coyote_time = value;
+                    }
+                }
+                "JumpBufferTime" => {
+                    if let FieldValue::Float(Some(value)) = field.value {
+                        actor.jump_buffer_time = value;
+                    }
+                }
+                "HitstunTime" => {
+                    if let FieldValue::Float(Some(value)) = field.value {
+                        actor.hitstun_time = value;
+                    }
+                }
+                "Health" => {
+                    if let FieldValue::Int(Some(value)) = field.value {
+                        health = Health::new(value.max(1) as u32, health.invulnerable_time);
+                    }
+                }
+                "InvulnerableTime" => {
+                    if let FieldValue::Float(Some(value)) = field.value {
+                        health.invulnerable_time = value;
+                    }
+                }
                 "Index" => {
                     if let FieldValue::Int(Some(value)) = field.value {
                         index = value as usize;
@@ -124,9 +248,22 @@ impl LdtkEntity for PlayerBundle {
             }
         }
         
+        PlayerBundle::new(index, &sprite_path, actor, health, asset_server, texture_atlases)
+    }
+}
+
+impl PlayerBundle {
+    pub fn new(
+        index: usize,
+        sprite_path: &str,
+        actor: Actor,
+        health: Health,
+        asset_server: &AssetServer,
+        texture_atlases: &mut Assets<TextureAtlas>,
+    ) -> Self {
         let texture_handle = asset_server.load(sprite_path);
         let texture_atlas =
-            TextureAtlas::from_grid(texture_handle, Vec2::new(24., 24.), 4, 8, None, None);
+            TextureAtlas::from_grid(texture_handle, Vec2::new(24., 24.), 4, 10, None, None);
         let texture_atlas_handle = texture_atlases.add(texture_atlas);
 
         PlayerBundle {
@@ -158,10 +295,23 @@ impl LdtkEntity for PlayerBundle {
                 air_timer: 0.,
                 left_wall: false,
                 right_wall: false,
-                event: None,
+                left_wall_entity: None,
+                right_wall_entity: None,
                 last_dt: 1.,
                 carried: false,
                 carrying: false,
+                wall_sliding: false,
+                wall_jump_lock: 0.,
+                jump_held: false,
+                jump_state: JumpState::Grounded,
+                jump_buffer: None,
+                knockback: None,
+                hitstun: 0.,
+                launch: None,
+                thrown: false,
+                pushing: None,
+                yelling: 0.,
+                attacking: 0.,
             },
             actor_anim: ActorAnimationStates {
                 idle_row: 0,
@@ -172,15 +322,19 @@ impl LdtkEntity for PlayerBundle {
                 run_carry_row: 5,
                 push_row: 6,
                 yell_row: 7,
+                wall_slide_row: 8,
+                wall_jump_row: 9,
             },
             actor_audio: ActorAudio {
                 jump: asset_server.load("audio/jump_carpet_1.ogg"),
                 land: asset_server.load("audio/land_carpet_1.ogg"),
                 hit: asset_server.load("audio/hit.ogg"),
                 death: asset_server.load("audio/death1.ogg"),
-                pickup: asset_server.load("audio/pickup1.ogg"),
                 unlocked: asset_server.load("audio/unlocked.ogg"),
                 victory: asset_server.load("audio/victory.ogg"),
+                wall_slide: asset_server.load("audio/land2.ogg"),
+                wall_jump: asset_server.load("audio/jump2.ogg"),
+                attack: asset_server.load("audio/attack1.ogg"),
             },
             pickup_collector: crate::pickup::PickupCollector,
             squashy: Squashy {
@@ -197,48 +351,65 @@ impl LdtkEntity for PlayerBundle {
             carrier: Carrier {
                 carrying: None,
                 carry_offset: Vec3 {x: 0., y: 16., z:0. },
+                throw_speed: Vec2::new(180., 140.),
             },
             carryable: Carryable{},
+            render_interpolation: RenderInterpolation::default(),
+            health,
+            yeller: Yeller::default(),
+            attacker: Attacker::default(),
         }
     }
 }
 
-fn player_inputs(
-    actions: Res<Actions>,
-    mut player_query: Query<(&mut Actor, &ActorStatus, &Player)>,
+pub fn player_inputs(
+    mut actions: ResMut<Actions>,
+    mut player_query: Query<(&mut Actor, &Player, Option<&Dying>)>,
 ) {
     
-    for (mut actor, status, player) in &mut player_query {
-        let input = Vec2::new(actions.player_movement[player.index].x, actions.player_movement[player.index].y);
-        actor.jump_input = actions.jump[player.index];
-        actor.grab_input = actions.action[player.index];
-        actor.can_jump = status.grounded || status.air_timer < actor.jump_time;
+    for (mut actor, player, dying) in &mut player_query {
+        let mut player_actions = actions.take_player(player.index);
+        if dying.is_some() {
+            player_actions = default();
+        }
+        let input = player_actions.movement;
+        // A tap that was released before this tick still counts as held for one tick
+        actor.jump_input = player_actions.jump || player_actions.jump_pressed;
+        actor.grab_input = player_actions.action;
+        actor.yell_input = player_actions.yell;
+        actor.attack_input = player_actions.attack;
         actor.move_input = input.x;
+        actor.aim_input = input;
     }
 }
 
 fn player_win(
     mut next_level_writer: EventWriter<ChangeLevelEvent>,
     rapier_context: Res<RapierContext>,
+    mut event_writer: EventWriter<ActorTickEvent>,
+    level_souls: Res<LevelSouls>,
     mut doors: Query<&mut Door>,
-    mut player_query: Query<(&Transform, &mut ActorStatus), With<Player>>,
+    player_query: Query<(Entity, &Transform), (With<Player>, Without<Dying>)>,
 ) {
-    for (transform, mut status) in &mut player_query {
+    for (player, transform) in &player_query {
         let shape = Collider::capsule_y(5.5, 3.5);
         let filter = QueryFilter::new();
         let shape_pos = transform.translation.truncate();
 
         rapier_context.intersections_with_shape(shape_pos, 0., &shape, filter, |entity| -> bool {
             if let Ok(mut door) = doors.get_mut(entity) {
-                if door.required_souls == 0 {
+                if door.is_open(&level_souls) && !door.entered {
                     next_level_writer.send(ChangeLevelEvent {
                         index: door.next_level,
                         completed: true,
                         win_game: door.next_level == 32767,
                     });
-                    status.event = Some(ActorEvent::Win);
+                    event_writer.send(ActorTickEvent {
+                        actor: player,
+                        event: ActorEvent::Win,
+                    });
 
-                    door.required_souls = usize::MAX;
+                    door.entered = true;
 
                     return false; // no need to keep looking
                 }
@@ -248,24 +419,193 @@ fn player_win(
     }
 }
 
+fn record_spawn_points(
+    mut commands: Commands,
+    player_query: Query<(Entity, &Transform), (With<Player>, Without<SpawnPoint>)>,
+) {
+    for (entity, transform) in &player_query {
+        commands.entity(entity).insert(SpawnPoint(transform.translation));
+    }
+}
+
+/// Running out of health takes just that player out for a moment, the rest of the level carries on.
+/// Whatever they held is dropped, and they fall out of anyone's arms.
 fn player_death(
-    mut player_query: Query<(&Transform, &mut ActorStatus), With<Player>>,
-    enemies_query: Query<Entity, With<TouchDeath>>,
-    mut reload_writer: EventWriter<ReloadWorldEvent>,
-    rapier_context: Res<RapierContext>,
+    mut commands: Commands,
+    player_query: Query<(Entity, &Health, Option<&Carried>), (With<Player>, Changed<Health>, Without<Dying>)>,
+    mut carrier_query: Query<&mut Carrier>,
+    mut end_carry_writer: EventWriter<EndCarryEvent>,
+    mut death_writer: EventWriter<PlayerDeathEvent>,
 ) {
-    for (transform, mut status) in &mut player_query {
-        let shape = Collider::capsule_y(5.5, 3.5);
-        let filter = QueryFilter::new();
-        let shape_pos = transform.translation.truncate();
+    for (player_entity, health, carried) in &player_query {
+        if !health.is_dead() {
+            continue;
+        }
 
-        rapier_context.intersections_with_shape(shape_pos, 0., &shape, filter, |entity| -> bool {
-            if let Ok(_touched_ent) = enemies_query.get(entity) {
-                reload_writer.send(ReloadWorldEvent);
-                status.event = Some(ActorEvent::Died);
-                return false; // no need to keep looking
+        if let Ok(mut carrier) = carrier_query.get_mut(player_entity) {
+            if let Some(carried_entity) = carrier.carrying.take() {
+                end_carry_writer.send(EndCarryEvent {
+                    carrier: player_entity,
+                    picked_up: carried_entity,
+                    release: CarryRelease::Drop,
+                });
+                commands.entity(carried_entity).remove::<Carried>();
             }
-            true
+        }
+
+        if let Some(carried) = carried {
+            if let Ok(mut carrier) = carrier_query.get_mut(carried.held_by) {
+                carrier.carrying = None;
+            }
+            end_carry_writer.send(EndCarryEvent {
+                carrier: carried.held_by,
+                picked_up: player_entity,
+                release: CarryRelease::Drop,
+            });
+            commands.entity(player_entity).remove::<Carried>();
+        }
+
+        commands.entity(player_entity).insert(Dying { timer: DEATH_TIME });
+        death_writer.send(PlayerDeathEvent {
+            player: player_entity,
         });
     }
 }
+
+/// Brings dead players back at the last checkpoint, or where they started the level,
+/// with full health and a moment of invulnerability to get their bearings
+fn player_respawn(
+    mut commands: Commands,
+    fixed_time: Res<FixedTime>,
+    active_checkpoint: Res<ActiveCheckpoint>,
+    mut player_query: Query<(
+        Entity,
+        &mut Dying,
+        &SpawnPoint,
+        &mut Transform,
+        &mut Health,
+        &mut ActorStatus,
+        &mut KinematicCharacterController,
+        Option<&mut RenderInterpolation>,
+    )>,
+) {
+    let dt = fixed_time.period.as_secs_f32();
+
+    for (
+        entity,
+        mut dying,
+        spawn_point,
+        mut transform,
+        mut health,
+        mut status,
+        mut controller,
+        interpolation,
+    ) in &mut player_query
+    {
+        dying.timer -= dt;
+        if dying.timer > 0. {
+            continue;
+        }
+
+        let respawn = active_checkpoint.position.unwrap_or(spawn_point.0);
+        transform.translation = respawn.truncate().extend(transform.translation.z);
+        controller.translation = None;
+
+        if let Some(mut interpolation) = interpolation {
+            interpolation.snap(transform.translation);
+        }
+
+        *health = Health {
+            invulnerable: health.invulnerable_time,
+            ..Health::new(health.max, health.invulnerable_time)
+        };
+
+        *status = ActorStatus {
+            facing_left: status.facing_left,
+            last_dt: status.last_dt,
+            ..default()
+        };
+
+        commands.entity(entity).remove::<Dying>();
+    }
+}
+
+/// Dead players tumble upside down and fade out before respawning
+fn death_animation(mut player_query: Query<(Option<&Dying>, &mut TextureAtlasSprite), With<Player>>) {
+    for (dying, mut sprite) in &mut player_query {
+        sprite.flip_y = dying.is_some();
+
+        if let Some(dying) = dying {
+            sprite.color.set_a((dying.timer / DEATH_TIME).clamp(0., 1.));
+        }
+    }
+}
+
+/// Spawns joined players the level has no `Player` entity for where a respawn would put them,
+/// at the active checkpoint or the first player's spawn, and removes them again once their gamepad
+/// has left. Players placed by the level always stay.
+fn sync_local_players(
+    mut commands: Commands,
+    local_players: Res<LocalPlayers>,
+    active_checkpoint: Res<ActiveCheckpoint>,
+    player_query: Query<(
+        Entity,
+        &Player,
+        &Transform,
+        Option<&SpawnPoint>,
+        &Actor,
+        &Health,
+        Option<&Parent>,
+        Option<&JoinedPlayer>,
+    )>,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    // Nothing to copy from until the level has spawned its players
+    let Some((_, _, first_transform, first_spawn, first_actor, first_health, first_parent, _)) =
+        player_query
+            .iter()
+            .min_by_key(|(_, player, ..)| player.index)
+    else {
+        return;
+    };
+
+    // A first player without a spawn point yet hasn't moved from it
+    let spawn_point = first_spawn.map_or(first_transform.translation, |spawn| spawn.0);
+    let position = active_checkpoint
+        .position
+        .unwrap_or(spawn_point)
+        .truncate()
+        .extend(first_transform.translation.z);
+
+    for (entity, player, .., joined) in &player_query {
+        if joined.is_some() && !local_players.is_joined(player.index) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    for index in local_players.joined() {
+        if player_query.iter().any(|(_, player, ..)| player.index == index) {
+            continue;
+        }
+
+        let mut bundle = PlayerBundle::new(
+            index,
+            PLAYER_SPRITES[index % PLAYER_SPRITES.len()],
+            first_actor.clone(),
+            Health::new(first_health.max, first_health.invulnerable_time),
+            &asset_server,
+            &mut texture_atlases,
+        );
+        bundle.sprite_sheet_bundle.transform = Transform::from_translation(position);
+
+        let new_player = commands
+            .spawn((bundle, SpawnPoint(spawn_point), JoinedPlayer))
+            .id();
+
+        // Parent under the same level so the new player goes away with it
+        if let Some(parent) = first_parent {
+            commands.entity(parent.get()).add_child(new_player);
+        }
+    }
+}
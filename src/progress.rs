@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use crate::door::LevelSouls;
+use crate::pickup::{PickupEvent, PickupType};
+use crate::player::PlayerDeathEvent;
+use crate::world::{switch_level, ChangeLevelEvent};
+use crate::GameState;
+
+pub struct ProgressPlugin;
+
+/// Which levels the player has reached and finished, by index into the LDtk level list
+#[derive(Resource, Debug, Clone)]
+pub struct LevelProgress {
+    pub unlocked: HashSet<usize>,
+    pub completed: HashSet<usize>,
+    /// Most souls collected in a single clear of each level
+    pub souls: HashMap<usize, usize>,
+    /// Fastest clear of each level, in seconds
+    pub best_times: HashMap<usize, f32>,
+}
+
+impl Default for LevelProgress {
+    fn default() -> Self {
+        LevelProgress {
+            unlocked: HashSet::from([0]),
+            completed: HashSet::new(),
+            souls: HashMap::new(),
+            best_times: HashMap::new(),
+        }
+    }
+}
+
+impl LevelProgress {
+    pub fn is_unlocked(&self, index: usize) -> bool {
+        self.unlocked.contains(&index)
+    }
+
+    pub fn is_completed(&self, index: usize) -> bool {
+        self.completed.contains(&index)
+    }
+}
+
+/// Statistics for the current run, from starting a new game until the win screen
+#[derive(Resource, Debug, Default, Clone)]
+pub struct RunStats {
+    pub total_time: f32,
+    pub deaths: usize,
+    pub souls: usize,
+    /// Time spent in the level currently being played
+    pub level_time: f32,
+    /// Level index and time taken for each level finished this run, in order
+    pub level_times: Vec<(usize, f32)>,
+}
+
+impl Plugin for ProgressPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelProgress>()
+            .init_resource::<RunStats>()
+            .add_systems(Update, track_level_progress.before(switch_level))
+            .add_systems(
+                Update,
+                (track_run_time, track_run_events).run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Formats a duration in seconds as minutes:seconds.hundredths
+pub fn format_time(seconds: f32) -> String {
+    let minutes = (seconds / 60.).floor();
+    format!("{}:{:05.2}", minutes as u32, seconds - minutes * 60.)
+}
+
+pub fn track_level_progress(
+    mut change_event_listener: EventReader<ChangeLevelEvent>,
+    level_selection: Res<LevelSelection>,
+    level_souls: Res<LevelSouls>,
+    mut progress: ResMut<LevelProgress>,
+    mut stats: ResMut<RunStats>,
+) {
+    for ev in change_event_listener.iter() {
+        if ev.completed {
+            if let LevelSelection::Index(current) = *level_selection {
+                progress.completed.insert(current);
+
+                let level_time = stats.level_time;
+                stats.level_times.push((current, level_time));
+
+                let best_time = progress.best_times.entry(current).or_insert(level_time);
+                *best_time = best_time.min(level_time);
+
+                let souls = progress.souls.entry(current).or_default();
+                *souls = (*souls).max(level_souls.collected);
+            }
+        }
+
+        stats.level_time = 0.;
+
+        if !ev.win_game {
+            progress.unlocked.insert(ev.index);
+        }
+    }
+}
+
+fn track_run_time(time: Res<Time>, mut stats: ResMut<RunStats>) {
+    stats.total_time += time.delta_seconds();
+    stats.level_time += time.delta_seconds();
+}
+
+fn track_run_events(
+    mut death_events: EventReader<PlayerDeathEvent>,
+    mut pickup_events: EventReader<PickupEvent>,
+    mut stats: ResMut<RunStats>,
+) {
+    stats.deaths += death_events.iter().count();
+    stats.souls += pickup_events
+        .iter()
+        .filter(|ev| ev.pickup_type == PickupType::Soul)
+        .count();
+}
@@ -0,0 +1,341 @@
+use bevy::{prelude::*, sprite::Anchor};
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    actor::{
+        actor_carry, actor_movement, Actor, ActorStatus, Carried, Carryable, CarryRelease,
+        EndCarryEvent,
+    },
+    fixed_step::{RenderInterpolation, SimulationSet},
+    player::player_inputs,
+    world::Labeled,
+    GameState,
+};
+
+pub struct PropPlugin;
+
+/// Props are all the same size, so they stack and sit on ledges the same way
+const PROP_HALF_SIZE: f32 = 8.;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PropType {
+    Crate,
+    Rock,
+    Key,
+}
+
+/// Something lying around the level that can be carried, thrown and stood on
+#[derive(Component, Clone, Default)]
+pub struct Prop;
+
+/// Too heavy to carry, but moves when walked into
+#[derive(Component, Clone, Debug)]
+pub struct Pushable {
+    /// How fast the block and whoever pushes it go
+    pub speed: f32,
+}
+
+impl Default for Pushable {
+    fn default() -> Self {
+        Pushable { speed: 40. }
+    }
+}
+
+#[derive(Clone, Default, Bundle)]
+pub struct PropBundle {
+    pub sprite_bundle: SpriteBundle,
+    pub rigidbody: RigidBody,
+    pub collider: Collider,
+    pub controller: KinematicCharacterController,
+    pub label: Labeled,
+    pub actor: Actor,
+    pub actor_status: ActorStatus,
+    pub carryable: Carryable,
+    pub render_interpolation: RenderInterpolation,
+    pub prop: Prop,
+}
+
+impl PropType {
+    fn from_identifier(identifier: &str) -> Option<PropType> {
+        match identifier {
+            "Crate" => Some(PropType::Crate),
+            "Rock" => Some(PropType::Rock),
+            "KeyProp" => Some(PropType::Key),
+            _ => None,
+        }
+    }
+
+    pub fn default_sprite_path(&self) -> &'static str {
+        match self {
+            PropType::Crate => "sprites/crate.png",
+            PropType::Rock => "sprites/rock.png",
+            PropType::Key => "sprites/key.png",
+        }
+    }
+
+    /// Lines the bottom of the art up with the bottom of the collider
+    fn sprite_anchor(&self) -> Anchor {
+        match self {
+            PropType::Key => Anchor::Custom(Vec2::new(0., 0.4)),
+            _ => Anchor::Center,
+        }
+    }
+}
+
+impl PropBundle {
+    pub fn new(prop_type: PropType, sprite_path: &str, asset_server: &AssetServer) -> Self {
+        PropBundle {
+            sprite_bundle: SpriteBundle {
+                texture: asset_server.load(sprite_path),
+                sprite: Sprite {
+                    anchor: prop_type.sprite_anchor(),
+                    ..default()
+                },
+                transform: Transform::from_translation(Vec3::new(0., 0., 0.8)),
+                ..default()
+            },
+            rigidbody: RigidBody::KinematicPositionBased,
+            collider: Collider::cuboid(PROP_HALF_SIZE, PROP_HALF_SIZE),
+            controller: KinematicCharacterController {
+                offset: CharacterLength::Absolute(0.5),
+                autostep: None,
+                filter_flags: QueryFilterFlags::EXCLUDE_SENSORS,
+                ..default()
+            },
+            label: Labeled {
+                name: format!("{:?}", prop_type).to_lowercase(),
+            },
+            // No one steers a prop, it only falls, and slides to a stop after a throw
+            actor: Actor {
+                move_speed: 0.,
+                ..default()
+            },
+            actor_status: ActorStatus {
+                last_dt: 1.,
+                ..default()
+            },
+            carryable: Carryable {},
+            render_interpolation: RenderInterpolation::default(),
+            prop: Prop,
+        }
+    }
+}
+
+impl LdtkEntity for PropBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        _layer_instance: &LayerInstance,
+        _tileset: Option<&Handle<Image>>,
+        _tileset_definition: Option<&TilesetDefinition>,
+        asset_server: &AssetServer,
+        _texture_atlases: &mut Assets<TextureAtlas>,
+    ) -> Self {
+        let prop_type = PropType::from_identifier(&entity_instance.identifier)
+            .expect("Prop bundle registered for an unknown LDtk entity");
+
+        let mut sprite_path: String = prop_type.default_sprite_path().into();
+
+        for field in entity_instance.field_instances.iter() {
+            match field.identifier.as_str() {
+                "SpritePath" => {
+                    if let FieldValue::String(Some(value)) = &field.value {
+                        sprite_path = value.clone();
+                    }
+                }
+                unknown => println!("Unknown field \"{}\" on LDtk prop object!", unknown),
+            }
+        }
+
+        PropBundle::new(prop_type, &sprite_path, asset_server)
+    }
+}
+
+#[derive(Clone, Default, Bundle)]
+pub struct BlockBundle {
+    pub sprite_bundle: SpriteBundle,
+    pub rigidbody: RigidBody,
+    pub collider: Collider,
+    pub controller: KinematicCharacterController,
+    pub label: Labeled,
+    pub actor: Actor,
+    pub actor_status: ActorStatus,
+    pub render_interpolation: RenderInterpolation,
+    pub pushable: Pushable,
+}
+
+impl BlockBundle {
+    pub fn new(pushable: Pushable, asset_server: &AssetServer) -> Self {
+        BlockBundle {
+            sprite_bundle: SpriteBundle {
+                texture: asset_server.load("sprites/block.png"),
+                transform: Transform::from_translation(Vec3::new(0., 0., 0.8)),
+                ..default()
+            },
+            rigidbody: RigidBody::KinematicPositionBased,
+            collider: Collider::cuboid(PROP_HALF_SIZE, PROP_HALF_SIZE),
+            controller: KinematicCharacterController {
+                offset: CharacterLength::Absolute(0.5),
+                autostep: None,
+                filter_flags: QueryFilterFlags::EXCLUDE_SENSORS,
+                ..default()
+            },
+            label: Labeled {
+                name: String::from("block"),
+            },
+            // Moved by its pushers through `move_input`, and stops dead when they let go
+            actor: Actor {
+                move_speed: pushable.speed,
+                accel: 2000.,
+                deccel: 2000.,
+                drag: 1.,
+                ..default()
+            },
+            actor_status: ActorStatus {
+                last_dt: 1.,
+                ..default()
+            },
+            render_interpolation: RenderInterpolation::default(),
+            pushable,
+        }
+    }
+}
+
+impl LdtkEntity for BlockBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        _layer_instance: &LayerInstance,
+        _tileset: Option<&Handle<Image>>,
+        _tileset_definition: Option<&TilesetDefinition>,
+        asset_server: &AssetServer,
+        _texture_atlases: &mut Assets<TextureAtlas>,
+    ) -> Self {
+        let mut pushable = Pushable::default();
+
+        for field in entity_instance.field_instances.iter() {
+            match field.identifier.as_str() {
+                "PushSpeed" => {
+                    if let FieldValue::Float(Some(value)) = field.value {
+                        pushable.speed = value;
+                    }
+                }
+                unknown => println!("Unknown field \"{}\" on LDtk block object!", unknown),
+            }
+        }
+
+        BlockBundle::new(pushable, asset_server)
+    }
+}
+
+impl Plugin for PropPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, push_blocks
+                .after(player_inputs)
+                .before(actor_movement)
+                .in_set(SimulationSet)
+                .run_if(in_state(GameState::Playing))
+            )
+            .add_systems(FixedUpdate, (
+                set_down_props,
+                carried_props_pass_through,
+            ).chain().after(actor_carry).in_set(SimulationSet).run_if(in_state(GameState::Playing)))
+            ;
+    }
+}
+
+/// A dropped prop is put down in front of its carrier where there is room,
+/// rather than landing on top of them
+fn set_down_props(
+    mut end_carry_events: EventReader<EndCarryEvent>,
+    rapier_context: Res<RapierContext>,
+    carrier_query: Query<(&Transform, &ActorStatus), Without<Prop>>,
+    mut prop_query: Query<(&mut Transform, Option<&mut RenderInterpolation>), With<Prop>>,
+) {
+    for ev in end_carry_events.iter() {
+        if ev.release != CarryRelease::Drop {
+            continue;
+        }
+
+        let Ok((carrier_transform, carrier_status)) = carrier_query.get(ev.carrier) else {
+            continue;
+        };
+        let Ok((mut transform, interpolation)) = prop_query.get_mut(ev.picked_up) else {
+            continue;
+        };
+
+        let forward = if carrier_status.facing_left { -1. } else { 1. };
+        let position = carrier_transform.translation.truncate()
+            + Vec2::new(forward * (PROP_HALF_SIZE + 4.), 0.);
+
+        let shape = Collider::cuboid(PROP_HALF_SIZE - 0.5, PROP_HALF_SIZE - 0.5);
+        let filter = QueryFilter::new()
+            .exclude_sensors()
+            .exclude_collider(ev.picked_up);
+        if rapier_context
+            .intersection_with_shape(position, 0., &shape, filter)
+            .is_some()
+        {
+            continue;
+        }
+
+        transform.translation = position.extend(transform.translation.z);
+        if let Some(mut interpolation) = interpolation {
+            interpolation.snap(transform.translation);
+        }
+    }
+}
+
+/// Actors on the ground walking into a block move it along with them.
+/// Blocks are actors too, so a pushed block pushes the next one a tick later.
+fn push_blocks(mut actor_query: Query<(Entity, &mut Actor, &mut ActorStatus, Option<&Pushable>)>) {
+    let mut pushes = Vec::new();
+
+    for (pusher, actor, status, _) in &actor_query {
+        let wall = if actor.move_input > 0.1 {
+            status.right_wall_entity
+        } else if actor.move_input < -0.1 {
+            status.left_wall_entity
+        } else {
+            None
+        };
+
+        let Some(block) = wall.filter(|_| status.grounded) else {
+            continue;
+        };
+
+        if let Ok((_, _, _, Some(pushable))) = actor_query.get(block) {
+            pushes.push((pusher, block, actor.move_input.signum(), pushable.speed));
+        }
+    }
+
+    for (_, mut actor, mut status, pushable) in &mut actor_query {
+        status.pushing = None;
+        if pushable.is_some() {
+            actor.move_input = 0.;
+        }
+    }
+
+    for (pusher, block, direction, speed) in pushes {
+        if let Ok((_, mut actor, ..)) = actor_query.get_mut(block) {
+            actor.move_input = direction;
+        }
+
+        if let Ok((_, _, mut status, _)) = actor_query.get_mut(pusher) {
+            status.pushing = Some(speed);
+        }
+    }
+}
+
+/// Props are solid to stand on, except while carried, so they don't block their carrier's jumps
+fn carried_props_pass_through(
+    mut commands: Commands,
+    prop_query: Query<(Entity, Option<&Carried>, Option<&Sensor>), With<Prop>>,
+) {
+    for (entity, carried, sensor) in &prop_query {
+        if carried.is_some() && sensor.is_none() {
+            commands.entity(entity).insert(Sensor);
+        } else if carried.is_none() && sensor.is_some() {
+            commands.entity(entity).remove::<Sensor>();
+        }
+    }
+}
@@ -0,0 +1,427 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::actions::{Actions, LocalPlayers, PlayerActions, PlayerSlot};
+use crate::fixed_step::SimulationSet;
+use crate::player::player_inputs;
+use crate::world::ChangeLevelEvent;
+use crate::{storage, GameState};
+
+pub struct ReplayPlugin;
+
+/// Bump this whenever something changes that makes old recordings play back differently
+const REPLAY_VERSION: u32 = 1;
+
+/// Ticks a verification keeps running after the recorded input runs out before giving up
+const VERIFY_GRACE_TICKS: u32 = 120;
+
+/// Where the replay being recorded goes when saved by hand
+const LATEST_REPLAY_PATH: &str = "replays/latest.ron";
+
+/// Everything needed to play back a run of one level.
+/// Nothing in the simulation is random, so the input alone plays it back the same way.
+/// Anything random added later needs its seed recorded here, and `REPLAY_VERSION` bumped.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Replay {
+    pub version: u32,
+    pub level: usize,
+    /// Players that had joined when the level was started
+    pub players: Vec<usize>,
+    /// One entry per attempt, each starting from the level (re)spawning
+    pub attempts: Vec<ReplayAttempt>,
+}
+
+/// Input for every tick of one attempt, with identical ticks run-length encoded
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ReplayAttempt {
+    pub runs: Vec<(u32, Vec<PlayerActions>)>,
+}
+
+impl ReplayAttempt {
+    fn push(&mut self, players: &[PlayerActions]) {
+        match self.runs.last_mut() {
+            Some((count, last)) if last.as_slice() == players => *count += 1,
+            _ => self.runs.push((1, players.to_vec())),
+        }
+    }
+}
+
+impl Replay {
+    pub fn load(path: &str) -> Result<Replay, String> {
+        let text = storage::read(path).ok_or_else(|| format!("could not read {}", path))?;
+        let replay: Replay = ron::from_str(&text).map_err(|err| err.to_string())?;
+
+        if replay.version != REPLAY_VERSION {
+            return Err(format!(
+                "replay version {} does not match the supported version {}",
+                replay.version, REPLAY_VERSION
+            ));
+        }
+
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: &str) {
+        let result = ron::to_string(self)
+            .map_err(|err| err.to_string())
+            .and_then(|text| storage::write(path, &text));
+
+        match result {
+            Ok(()) => info!("Saved replay of level {} to {}", self.level, path),
+            Err(err) => error!("Failed to save replay to {}: {}", path, err),
+        }
+    }
+}
+
+/// Picked from the command line, see `ReplayOptions::from_args`
+#[derive(Resource, Debug, Clone)]
+pub struct ReplayOptions {
+    pub path: String,
+    /// Run without a window and exit once the replay has finished
+    pub verify: bool,
+}
+
+impl ReplayOptions {
+    /// `--replay <file>` plays a recording back, `--verify <file>` checks it reaches the door
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Option<ReplayOptions> {
+        let mut options = None;
+
+        while let Some(arg) = args.next() {
+            let verify = match arg.as_str() {
+                "--replay" => false,
+                "--verify" => true,
+                _ => {
+                    warn!("Ignoring unknown argument {}", arg);
+                    continue;
+                }
+            };
+
+            match args.next() {
+                Some(path) => options = Some(ReplayOptions { path, verify }),
+                None => warn!("{} needs a replay file", arg),
+            }
+        }
+
+        options
+    }
+}
+
+/// How a `--verify` run ended, mapped to the process exit code by main.rs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyOutcome {
+    ReachedDoor,
+    RanOut,
+    Unreadable,
+}
+
+impl VerifyOutcome {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            VerifyOutcome::ReachedDoor => 0,
+            VerifyOutcome::RanOut => 1,
+            VerifyOutcome::Unreadable => 2,
+        }
+    }
+}
+
+/// Where a verification leaves its outcome. Shared with main.rs, which can still read it
+/// once the app has exited and been torn down.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct ReplayVerdict(Arc<Mutex<Option<VerifyOutcome>>>);
+
+impl ReplayVerdict {
+    pub fn get(&self) -> Option<VerifyOutcome> {
+        *self.0.lock().unwrap()
+    }
+
+    fn set(&self, outcome: VerifyOutcome) {
+        *self.0.lock().unwrap() = Some(outcome);
+    }
+}
+
+/// The run being recorded, only while no replay is playing
+#[derive(Resource, Default)]
+struct ReplayRecorder {
+    replay: Option<Replay>,
+}
+
+#[derive(Resource)]
+struct ReplayPlayback {
+    replay: Replay,
+    verify: bool,
+    started: bool,
+    attempt: Option<usize>,
+    run: usize,
+    offset: u32,
+    tick: u32,
+    total_ticks: u32,
+    idle_ticks: u32,
+}
+
+impl ReplayPlayback {
+    fn new(replay: Replay, verify: bool) -> Self {
+        ReplayPlayback {
+            replay,
+            verify,
+            started: false,
+            attempt: None,
+            run: 0,
+            offset: 0,
+            tick: 0,
+            total_ticks: 0,
+            idle_ticks: 0,
+        }
+    }
+
+    fn start_attempt(&mut self) {
+        self.attempt = Some(self.attempt.map_or(0, |attempt| attempt + 1));
+        self.run = 0;
+        self.offset = 0;
+        self.tick = 0;
+    }
+
+    fn next_tick(&mut self) -> Option<Vec<PlayerActions>> {
+        let attempt = self.replay.attempts.get(self.attempt?)?;
+        let (count, players) = attempt.runs.get(self.run)?;
+        let players = players.clone();
+
+        self.offset += 1;
+        if self.offset >= *count {
+            self.run += 1;
+            self.offset = 0;
+        }
+
+        self.tick += 1;
+        self.total_ticks += 1;
+        Some(players)
+    }
+}
+
+/// Every level run is recorded, completed runs are saved next to the other save data
+/// and F9 saves the run in progress.
+/// Playing a replay back feeds its recorded `Actions` to the simulation instead of the devices.
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplayRecorder>()
+            .add_systems(Update, level_spawned)
+            .add_systems(Update, (save_completed_replay, save_replay_hotkey))
+            .add_systems(OnEnter(GameState::Menu), start_playback
+                .run_if(resource_exists::<ReplayPlayback>())
+            )
+            .add_systems(FixedUpdate, record_actions
+                .in_set(SimulationSet)
+                .before(player_inputs)
+                .run_if(not(resource_exists::<ReplayPlayback>()))
+                .run_if(in_state(GameState::Playing))
+            )
+            .add_systems(FixedUpdate, play_back_actions
+                .in_set(SimulationSet)
+                .before(player_inputs)
+                .run_if(resource_exists::<ReplayPlayback>())
+                .run_if(in_state(GameState::Playing))
+            )
+            .add_systems(FixedUpdate, report_level_change
+                .after(SimulationSet)
+                .run_if(resource_exists::<ReplayPlayback>())
+            )
+            .add_systems(Update, exit_with_verdict)
+            ;
+
+        let Some(options) = app.world.get_resource::<ReplayOptions>().cloned() else {
+            return;
+        };
+
+        match Replay::load(&options.path) {
+            Ok(replay) => {
+                info!(
+                    "Playing back {} on level {} with {} attempts",
+                    options.path,
+                    replay.level,
+                    replay.attempts.len()
+                );
+                app.insert_resource(ReplayPlayback::new(replay, options.verify));
+            }
+            Err(err) => {
+                error!("Failed to load replay {}: {}", options.path, err);
+                if options.verify {
+                    app.world.get_resource_or_insert_with(ReplayVerdict::default)
+                        .set(VerifyOutcome::Unreadable);
+                }
+            }
+        }
+    }
+}
+
+/// Skips the menus and goes straight into the recorded level
+fn start_playback(
+    mut playback: ResMut<ReplayPlayback>,
+    mut level_selection: ResMut<LevelSelection>,
+    mut local_players: ResMut<LocalPlayers>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if playback.started {
+        return;
+    }
+    playback.started = true;
+
+    *level_selection = LevelSelection::Index(playback.replay.level);
+
+    let slots = playback.replay.players.iter().max().map_or(1, |last| last + 1);
+    local_players.slots = (0..slots)
+        .map(|index| playback.replay.players.contains(&index).then(PlayerSlot::default))
+        .collect();
+
+    next_state.set(GameState::Playing);
+}
+
+/// Each (re)spawn of the level starts a new attempt
+fn level_spawned(
+    mut level_events: EventReader<LevelEvent>,
+    level_selection: Res<LevelSelection>,
+    local_players: Res<LocalPlayers>,
+    mut recorder: ResMut<ReplayRecorder>,
+    playback: Option<ResMut<ReplayPlayback>>,
+) {
+    if !level_events.iter().any(|ev| matches!(ev, LevelEvent::Spawned(_))) {
+        return;
+    }
+
+    if let Some(mut playback) = playback {
+        playback.start_attempt();
+        return;
+    }
+
+    let LevelSelection::Index(level) = *level_selection else {
+        return;
+    };
+
+    // Respawning after a death keeps recording into the same replay
+    if recorder.replay.as_ref().map_or(true, |replay| replay.level != level) {
+        recorder.replay = Some(Replay {
+            version: REPLAY_VERSION,
+            level,
+            players: local_players.joined().collect(),
+            attempts: Vec::new(),
+        });
+    }
+
+    if let Some(replay) = recorder.replay.as_mut() {
+        replay.attempts.push(ReplayAttempt::default());
+    }
+}
+
+fn record_actions(mut recorder: ResMut<ReplayRecorder>, actions: Res<Actions>) {
+    let Some(attempt) = recorder
+        .replay
+        .as_mut()
+        .and_then(|replay| replay.attempts.last_mut())
+    else {
+        return;
+    };
+
+    attempt.push(&actions.players);
+}
+
+fn play_back_actions(
+    mut commands: Commands,
+    mut playback: ResMut<ReplayPlayback>,
+    mut actions: ResMut<Actions>,
+    verdict: Option<Res<ReplayVerdict>>,
+) {
+    // Whatever the devices did since the last tick is thrown away
+    if let Some(players) = playback.next_tick() {
+        actions.players = players;
+        return;
+    }
+
+    actions.players.fill(PlayerActions::default());
+
+    // Waiting for the level to spawn
+    if playback.attempt.is_none() {
+        return;
+    }
+
+    if !playback.verify {
+        info!("Replay finished after {} ticks", playback.total_ticks);
+        commands.remove_resource::<ReplayPlayback>();
+        return;
+    }
+
+    playback.idle_ticks += 1;
+    if playback.idle_ticks > VERIFY_GRACE_TICKS {
+        error!(
+            "Replay of level {} did not reach the door, input ran out after {} ticks",
+            playback.replay.level, playback.total_ticks
+        );
+        commands.remove_resource::<ReplayPlayback>();
+        if let Some(verdict) = verdict {
+            verdict.set(VerifyOutcome::RanOut);
+        }
+    }
+}
+
+fn report_level_change(
+    mut commands: Commands,
+    mut change_events: EventReader<ChangeLevelEvent>,
+    playback: Res<ReplayPlayback>,
+    verdict: Option<Res<ReplayVerdict>>,
+) {
+    for ev in change_events.iter() {
+        if !ev.completed {
+            continue;
+        }
+
+        info!(
+            "Replay of level {} reached the door on tick {} of attempt {} ({} ticks in total)",
+            playback.replay.level,
+            playback.tick,
+            playback.attempt.map_or(0, |attempt| attempt + 1),
+            playback.total_ticks
+        );
+
+        if playback.verify {
+            commands.remove_resource::<ReplayPlayback>();
+            if let Some(verdict) = &verdict {
+                verdict.set(VerifyOutcome::ReachedDoor);
+            }
+        }
+    }
+}
+
+/// Ends a verification once it has an outcome, letting the app shut down as usual
+fn exit_with_verdict(verdict: Option<Res<ReplayVerdict>>, mut exit: EventWriter<AppExit>) {
+    if verdict.is_some_and(|verdict| verdict.get().is_some()) {
+        exit.send(AppExit);
+    }
+}
+
+/// Completed runs are written out so they can be checked later with `--verify`
+fn save_completed_replay(
+    mut change_events: EventReader<ChangeLevelEvent>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    for ev in change_events.iter() {
+        if !ev.completed {
+            continue;
+        }
+
+        if let Some(replay) = recorder.replay.take() {
+            replay.save(&format!("replays/level_{}.ron", replay.level));
+        }
+    }
+}
+
+fn save_replay_hotkey(input: Res<Input<KeyCode>>, recorder: Res<ReplayRecorder>) {
+    if !input.just_pressed(KeyCode::F9) {
+        return;
+    }
+
+    match &recorder.replay {
+        Some(replay) => replay.save(LATEST_REPLAY_PATH),
+        None => warn!("Nothing has been recorded yet"),
+    }
+}
@@ -0,0 +1,281 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::progress::LevelProgress;
+use crate::ui_events::UiEvent;
+use crate::world::{switch_level, ChangeLevelEvent};
+use crate::{storage, GameState};
+
+pub struct SavePlugin;
+
+pub const SAVE_SLOTS: usize = 3;
+
+/// Bump this whenever the layout of `SaveData` changes and teach `SaveData::from_ron`
+/// how to upgrade saves written with the previous version.
+/// Adding a field with a sensible default does not need a new version.
+const SAVE_VERSION: u32 = 2;
+
+#[derive(Resource, Default, Debug)]
+pub struct SaveSlot {
+    pub index: usize,
+}
+
+/// Everything written to disk for one save slot
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct SaveData {
+    pub version: u32,
+    pub levels: BTreeMap<usize, LevelRecord>,
+    pub current_level: usize,
+}
+
+/// What the slot has done on one level
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct LevelRecord {
+    pub unlocked: bool,
+    pub completed: bool,
+    /// Most souls collected in a single clear
+    pub souls: Option<usize>,
+    /// Fastest clear, in seconds
+    pub best_time: Option<f32>,
+}
+
+/// Version 1 saves kept a separate collection for each kind of progress
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct SaveDataV1 {
+    unlocked_levels: BTreeSet<usize>,
+    completed_levels: BTreeSet<usize>,
+    souls: BTreeMap<usize, usize>,
+    best_times: BTreeMap<usize, f32>,
+    current_level: usize,
+}
+
+impl SaveDataV1 {
+    /// Gathers each level's progress into one record
+    fn upgrade(self) -> SaveData {
+        let progress = LevelProgress {
+            unlocked: self.unlocked_levels.into_iter().collect(),
+            completed: self.completed_levels.into_iter().collect(),
+            souls: self.souls.into_iter().collect(),
+            best_times: self.best_times.into_iter().collect(),
+        };
+
+        SaveData::from_progress(&progress, &LevelSelection::Index(self.current_level))
+    }
+}
+
+/// Just enough of a save to decide how to read the rest of it
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    UnsupportedVersion(u32),
+    Storage(String),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Parse(err) => write!(f, "could not parse save: {}", err),
+            SaveError::Serialize(err) => write!(f, "could not write save: {}", err),
+            SaveError::UnsupportedVersion(version) => write!(
+                f,
+                "save version {} is not supported, the current version is {}",
+                version, SAVE_VERSION
+            ),
+            SaveError::Storage(err) => write!(f, "could not store save: {}", err),
+        }
+    }
+}
+
+impl From<ron::error::SpannedError> for SaveError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        SaveError::Parse(err)
+    }
+}
+
+impl From<ron::Error> for SaveError {
+    fn from(err: ron::Error) -> Self {
+        SaveError::Serialize(err)
+    }
+}
+
+impl Default for SaveData {
+    fn default() -> Self {
+        SaveData::from_progress(&LevelProgress::default(), &LevelSelection::Index(0))
+    }
+}
+
+impl SaveData {
+    pub fn from_progress(progress: &LevelProgress, level_selection: &LevelSelection) -> Self {
+        let mut levels: BTreeMap<usize, LevelRecord> = BTreeMap::new();
+
+        for index in &progress.unlocked {
+            levels.entry(*index).or_default().unlocked = true;
+        }
+        for index in &progress.completed {
+            levels.entry(*index).or_default().completed = true;
+        }
+        for (index, souls) in &progress.souls {
+            levels.entry(*index).or_default().souls = Some(*souls);
+        }
+        for (index, time) in &progress.best_times {
+            levels.entry(*index).or_default().best_time = Some(*time);
+        }
+
+        SaveData {
+            version: SAVE_VERSION,
+            levels,
+            current_level: match level_selection {
+                LevelSelection::Index(index) => *index,
+                _ => 0,
+            },
+        }
+    }
+
+    pub fn apply(&self, progress: &mut LevelProgress, level_selection: &mut LevelSelection) {
+        let levels = || self.levels.iter().map(|(index, record)| (*index, record));
+        *progress = LevelProgress {
+            unlocked: levels()
+                .filter(|(_, record)| record.unlocked)
+                .map(|(index, _)| index)
+                .collect(),
+            completed: levels()
+                .filter(|(_, record)| record.completed)
+                .map(|(index, _)| index)
+                .collect(),
+            souls: levels()
+                .filter_map(|(index, record)| record.souls.map(|souls| (index, souls)))
+                .collect(),
+            best_times: levels()
+                .filter_map(|(index, record)| record.best_time.map(|time| (index, time)))
+                .collect(),
+        };
+        // The first level can always be played, even from a hand edited save
+        progress.unlocked.insert(0);
+
+        *level_selection = LevelSelection::Index(self.current_level);
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, SaveError> {
+        let header: SaveHeader = ron::from_str(text)?;
+
+        match header.version {
+            SAVE_VERSION => Ok(ron::from_str(text)?),
+            1 => Ok(ron::from_str::<SaveDataV1>(text)?.upgrade()),
+            version => Err(SaveError::UnsupportedVersion(version)),
+        }
+    }
+
+    pub fn to_ron(&self) -> Result<String, SaveError> {
+        Ok(ron::ser::to_string_pretty(
+            self,
+            ron::ser::PrettyConfig::default(),
+        )?)
+    }
+
+    pub fn load(slot: usize) -> Result<Option<Self>, SaveError> {
+        storage::read(&slot_path(slot))
+            .map(|text| SaveData::from_ron(&text))
+            .transpose()
+    }
+
+    pub fn store(&self, slot: usize) -> Result<(), SaveError> {
+        storage::write(&slot_path(slot), &self.to_ron()?).map_err(SaveError::Storage)
+    }
+}
+
+fn slot_path(slot: usize) -> String {
+    format!("saves/slot_{}.ron", slot)
+}
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SaveSlot>()
+            .add_systems(OnExit(GameState::Loading), load_active_slot)
+            .add_systems(
+                OnTransition {
+                    from: GameState::Paused,
+                    to: GameState::Menu,
+                },
+                save_active_slot,
+            )
+            .add_systems(Update, (save_events, save_on_level_complete.after(switch_level)));
+    }
+}
+
+fn load_slot_into(
+    slot: usize,
+    progress: &mut LevelProgress,
+    level_selection: &mut LevelSelection,
+) {
+    match SaveData::load(slot) {
+        Ok(data) => data.unwrap_or_default().apply(progress, level_selection),
+        Err(err) => {
+            warn!("Save slot {}: {}", slot, err);
+            SaveData::default().apply(progress, level_selection);
+        }
+    }
+}
+
+fn store_slot(slot: usize, progress: &LevelProgress, level_selection: &LevelSelection) {
+    if let Err(err) = SaveData::from_progress(progress, level_selection).store(slot) {
+        warn!("Save slot {}: {}", slot, err);
+    }
+}
+
+fn load_active_slot(
+    slot: Res<SaveSlot>,
+    mut progress: ResMut<LevelProgress>,
+    mut level_selection: ResMut<LevelSelection>,
+) {
+    load_slot_into(slot.index, &mut progress, &mut level_selection);
+}
+
+fn save_active_slot(
+    slot: Res<SaveSlot>,
+    progress: Res<LevelProgress>,
+    level_selection: Res<LevelSelection>,
+) {
+    store_slot(slot.index, &progress, &level_selection);
+}
+
+fn save_on_level_complete(
+    mut change_event_listener: EventReader<ChangeLevelEvent>,
+    slot: Res<SaveSlot>,
+    progress: Res<LevelProgress>,
+    level_selection: Res<LevelSelection>,
+) {
+    if change_event_listener.iter().any(|ev| ev.completed) {
+        store_slot(slot.index, &progress, &level_selection);
+    }
+}
+
+fn save_events(
+    mut ui_events: EventReader<UiEvent>,
+    mut slot: ResMut<SaveSlot>,
+    mut progress: ResMut<LevelProgress>,
+    mut level_selection: ResMut<LevelSelection>,
+) {
+    for event in ui_events.iter() {
+        match event {
+            UiEvent::LoadGame => load_slot_into(slot.index, &mut progress, &mut level_selection),
+            UiEvent::NewGame => *progress = LevelProgress::default(),
+            UiEvent::NextSaveSlot => {
+                slot.index = (slot.index + 1) % SAVE_SLOTS;
+                load_slot_into(slot.index, &mut progress, &mut level_selection);
+            }
+            _ => (),
+        }
+    }
+}
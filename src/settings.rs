@@ -0,0 +1,187 @@
+use bevy::audio::{Volume, VolumeLevel};
+use bevy::prelude::*;
+use bevy::window::{PresentMode, PrimaryWindow, WindowMode};
+use serde::{Deserialize, Serialize};
+
+use crate::actions::InputMap;
+use crate::storage;
+use crate::ui_events::UiEvent;
+
+pub struct SettingsPlugin;
+
+const SETTINGS_PATH: &str = "settings.ron";
+
+/// Player preferences, read from `settings.ron` at startup and written back whenever they are
+/// changed in the menus.
+/// Anything missing from the file falls back to its default.
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub resolution: (f32, f32),
+    pub audio: AudioSettings,
+    pub accessibility: AccessibilitySettings,
+    pub controls: InputMap,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub master_volume: f32,
+    pub effects_volume: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    /// Skip flickering and flashing effects
+    pub reduce_flashing: bool,
+    pub ui_scale: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            fullscreen: false,
+            vsync: true,
+            resolution: (1200., 800.),
+            audio: AudioSettings::default(),
+            accessibility: AccessibilitySettings::default(),
+            controls: InputMap::default(),
+        }
+    }
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings {
+            master_volume: 1.,
+            effects_volume: 1.,
+        }
+    }
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        AccessibilitySettings {
+            reduce_flashing: false,
+            ui_scale: 1.,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        let Some(text) = storage::read(SETTINGS_PATH) else {
+            return Settings::default();
+        };
+
+        ron::from_str(&text).unwrap_or_else(|err| {
+            warn!("Could not read {}, using default settings: {}", SETTINGS_PATH, err);
+            Settings::default()
+        })
+    }
+
+    pub fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())
+            .and_then(|text| storage::write(SETTINGS_PATH, &text));
+
+        if let Err(err) = result {
+            error!("Could not write {}: {}", SETTINGS_PATH, err);
+        }
+    }
+
+    pub fn window_mode(&self) -> WindowMode {
+        if self.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        }
+    }
+
+    pub fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        }
+    }
+
+    /// Playback settings for a one shot sound effect at the configured volume
+    pub fn effect_playback(&self) -> PlaybackSettings {
+        PlaybackSettings::DESPAWN.with_volume(Volume::new_relative(self.audio.effects_volume))
+    }
+}
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        // main.rs loads the settings early so the window is created with them
+        if !app.world.contains_resource::<Settings>() {
+            app.insert_resource(Settings::load());
+        }
+
+        app.add_systems(
+            Update,
+            (settings_events, apply_settings.run_if(resource_changed::<Settings>())).chain(),
+        );
+    }
+}
+
+fn apply_settings(
+    settings: Res<Settings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    mut global_volume: ResMut<GlobalVolume>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    if let Ok(mut window) = window_query.get_single_mut() {
+        window.mode = settings.window_mode();
+        window.present_mode = settings.present_mode();
+    }
+
+    global_volume.volume = VolumeLevel::new(settings.audio.master_volume);
+    ui_scale.scale = settings.accessibility.ui_scale as f64;
+}
+
+/// Steps a 0 to 1 volume up by a tenth, wrapping back around to silent
+fn next_volume(volume: f32) -> f32 {
+    let step = (volume * 10.).round() as i32 + 1;
+    (step % 11) as f32 / 10.
+}
+
+/// Changes settings from the menus, saving them when anything changed
+fn settings_events(mut ui_events: EventReader<UiEvent>, mut settings: ResMut<Settings>) {
+    let mut changed = false;
+
+    for event in ui_events.iter() {
+        match event {
+            UiEvent::ToggleFullscreen => settings.fullscreen = !settings.fullscreen,
+            UiEvent::ToggleVsync => settings.vsync = !settings.vsync,
+            UiEvent::CycleMasterVolume => {
+                settings.audio.master_volume = next_volume(settings.audio.master_volume)
+            }
+            UiEvent::CycleEffectsVolume => {
+                settings.audio.effects_volume = next_volume(settings.audio.effects_volume)
+            }
+            UiEvent::ToggleReduceFlashing => {
+                settings.accessibility.reduce_flashing = !settings.accessibility.reduce_flashing
+            }
+            UiEvent::CycleUiScale => {
+                settings.accessibility.ui_scale = match settings.accessibility.ui_scale {
+                    scale if scale < 1.25 => 1.25,
+                    scale if scale < 1.5 => 1.5,
+                    scale if scale < 2. => 2.,
+                    _ => 1.,
+                }
+            }
+            _ => continue,
+        }
+
+        changed = true;
+    }
+
+    if changed {
+        settings.save();
+    }
+}
@@ -1,10 +1,12 @@
 use bevy::prelude::*;
 
+use crate::GameState;
+
 pub struct SpriteAnimationPlugin;
 
 impl Plugin for SpriteAnimationPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, animate_sprite);
+        app.add_systems(Update, animate_sprite.run_if(in_state(GameState::Playing)));
     }
 }
 
@@ -0,0 +1,40 @@
+//! Small text files such as settings and save games.
+//! Native builds read and write paths relative to the working directory,
+//! the browser build keeps the same paths as keys in localStorage.
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read(path: &str) -> Option<String> {
+    std::fs::read_to_string(path).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write(path: &str, contents: &str) -> Result<(), String> {
+    let path = std::path::Path::new(path);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    }
+    std::fs::write(path, contents).map_err(|err| err.to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn read(path: &str) -> Option<String> {
+    local_storage()?.get_item(&storage_key(path)).ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn write(path: &str, contents: &str) -> Result<(), String> {
+    local_storage()
+        .ok_or_else(|| String::from("localStorage is not available"))?
+        .set_item(&storage_key(path), contents)
+        .map_err(|err| format!("{:?}", err))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn storage_key(path: &str) -> String {
+    format!("platformer_game/{}", path)
+}
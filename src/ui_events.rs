@@ -1,12 +1,34 @@
 use bevy::{app::AppExit, prelude::*};
-// use bevy_pkv::PkvStore;
+use bevy_ecs_ldtk::prelude::*;
+use crate::actions::InputAction;
+use crate::progress::RunStats;
+use crate::world::ReloadWorldEvent;
 use crate::GameState;
 
-#[derive(Debug, Event)]
+#[derive(Debug, Clone, Event)]
 pub enum UiEvent {
-    _QuitGame,
-    _NewGame,
-    _LoadGame,
+    QuitGame,
+    NewGame,
+    LoadGame,
+    OpenMainMenu,
+    OpenLevelSelect,
+    OpenSettings,
+    CloseSettings,
+    Resume,
+    RestartLevel,
+    PlayLevel(usize),
+    NextSaveSlot,
+    ToggleFullscreen,
+    ToggleVsync,
+    CycleMasterVolume,
+    CycleEffectsVolume,
+    ToggleReduceFlashing,
+    CycleUiScale,
+    OpenControls,
+    CloseControls,
+    NextControlsPlayer,
+    Rebind(InputAction),
+    ResetControls,
 }
 
 pub struct UiEventPlugin;
@@ -24,14 +46,57 @@ impl Plugin for UiEventPlugin {
 fn event_reader(
     mut event_reader: EventReader<UiEvent>,
     mut exit: EventWriter<AppExit>,
+    state: Res<State<GameState>>,
     mut next_state:  ResMut<NextState<GameState>>,
-    // mut pkv: ResMut<PkvStore>,
+    mut settings_opened_from: Local<Option<GameState>>,
+    mut reload_writer: EventWriter<ReloadWorldEvent>,
+    mut run_stats: ResMut<RunStats>,
+    mut level_selection: ResMut<LevelSelection>,
 ) {
     for event in event_reader.iter() {
         match event {
-            UiEvent::_QuitGame => exit.send(AppExit),
-            UiEvent::_NewGame => next_state.set(GameState::Playing),
-            UiEvent::_LoadGame => next_state.set(GameState::Playing),
+            UiEvent::QuitGame => exit.send(AppExit),
+            UiEvent::NewGame => {
+                *level_selection = LevelSelection::Index(0);
+                *run_stats = RunStats::default();
+                next_state.set(GameState::Playing);
+            }
+            UiEvent::LoadGame => {
+                *run_stats = RunStats::default();
+                next_state.set(GameState::Playing);
+            }
+            UiEvent::OpenMainMenu => next_state.set(GameState::Menu),
+            UiEvent::OpenLevelSelect => next_state.set(GameState::LevelSelect),
+            UiEvent::OpenSettings => {
+                *settings_opened_from = Some(*state.get());
+                next_state.set(GameState::Settings);
+            }
+            UiEvent::CloseSettings => {
+                next_state.set(settings_opened_from.take().unwrap_or(GameState::Menu));
+            }
+            UiEvent::Resume => next_state.set(GameState::Playing),
+            UiEvent::PlayLevel(index) => {
+                *level_selection = LevelSelection::Index(*index);
+                run_stats.level_time = 0.;
+                next_state.set(GameState::Playing);
+            }
+            UiEvent::RestartLevel => {
+                reload_writer.send(ReloadWorldEvent);
+                next_state.set(GameState::Playing);
+            }
+            // Handled by the save plugin
+            UiEvent::NextSaveSlot => (),
+            // Handled by the settings plugin
+            UiEvent::ToggleFullscreen
+            | UiEvent::ToggleVsync
+            | UiEvent::CycleMasterVolume
+            | UiEvent::CycleEffectsVolume
+            | UiEvent::ToggleReduceFlashing
+            | UiEvent::CycleUiScale => (),
+            UiEvent::OpenControls => next_state.set(GameState::Controls),
+            UiEvent::CloseControls => next_state.set(GameState::Settings),
+            // Handled by the controls menu
+            UiEvent::NextControlsPlayer | UiEvent::Rebind(_) | UiEvent::ResetControls => (),
         }
     }
 }
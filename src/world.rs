@@ -3,7 +3,7 @@ use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 use std::collections::{HashMap, HashSet};
 
-use crate::GameState;
+use crate::{fixed_step::FIXED_TIMESTEP, loading::LevelAssets, GameState};
 
 pub struct WorldPlugin;
 
@@ -12,10 +12,10 @@ pub struct Labeled {
     pub name: String,
 }
 
-#[derive(Event)]
+#[derive(Event, Clone, Debug)]
 pub struct ReloadWorldEvent;
 
-#[derive(Event)]
+#[derive(Event, Clone, Debug)]
 pub struct ChangeLevelEvent {
     pub index: usize,
     pub completed: bool,
@@ -35,18 +35,42 @@ impl Plugin for WorldPlugin {
                 level_background: LevelBackground::Nonexistent,
                 ..default()
             })
-            .add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
+            // Physics systems are scheduled by the FixedStepPlugin
+            .add_plugins(
+                RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0)
+                    .with_default_system_setup(false),
+            )
             .insert_resource(RapierConfiguration {
                 gravity: Vec2::new(0.0, -2000.0),
+                timestep_mode: TimestepMode::Fixed {
+                    dt: FIXED_TIMESTEP,
+                    substeps: 1,
+                },
                 ..Default::default()
             })
-            .add_systems(OnEnter(GameState::Playing), setup_world)
-            .add_systems(OnExit(GameState::Playing), cleanup_world)
+            .add_systems(OnEnter(GameState::Playing), (setup_world, resume_physics))
+            .add_systems(OnExit(GameState::Playing), pause_physics)
+            // Pausing and the settings screen keep the world around underneath them,
+            // it is only torn down once we go back out to one of the other menus
+            .add_systems(OnEnter(GameState::Menu), cleanup_world)
+            .add_systems(OnEnter(GameState::LevelSelect), cleanup_world)
+            .add_systems(OnEnter(GameState::WinScreen), cleanup_world)
             .add_systems(Update, (switch_level, reload_level, spawn_wall_collision))
             
             .register_ldtk_entity::<crate::player::PlayerBundle>("Player")
             .register_ldtk_entity::<crate::door::DoorBundle>("Door")
-            .register_ldtk_entity::<WheatBundle>("Wheat")
+            .register_ldtk_entity::<crate::cuttable::WheatBundle>("Wheat")
+            .register_ldtk_entity::<crate::health::HazardBundle>("Hazard")
+            .register_ldtk_entity::<crate::checkpoint::CheckpointBundle>("Checkpoint")
+            .register_ldtk_entity::<crate::prop::PropBundle>("Crate")
+            .register_ldtk_entity::<crate::prop::PropBundle>("Rock")
+            .register_ldtk_entity::<crate::prop::PropBundle>("KeyProp")
+            .register_ldtk_entity::<crate::prop::BlockBundle>("Block")
+            .register_ldtk_entity::<crate::enemy::EnemyBundle>("Enemy")
+            .register_ldtk_entity::<crate::pickup::PickupBundle>("Soul")
+            .register_ldtk_entity::<crate::pickup::PickupBundle>("Coin")
+            .register_ldtk_entity::<crate::pickup::PickupBundle>("Health")
+            .register_ldtk_entity::<crate::pickup::PickupBundle>("Key")
             .register_ldtk_int_cell::<WallBundle>(1)
             .register_ldtk_int_cell::<SpikeBundle>(2);
 
@@ -59,12 +83,20 @@ impl Plugin for WorldPlugin {
     }
 }
 
-fn cleanup_world(mut commands: Commands, query: Query<Entity, Without<OrthographicProjection>>) {
-    for entity in query.iter() {
-        commands.entity(entity).despawn();
+fn cleanup_world(mut commands: Commands, world_query: Query<Entity, With<Handle<LdtkAsset>>>) {
+    for entity in world_query.iter() {
+        commands.entity(entity).despawn_recursive();
     }
 }
 
+fn pause_physics(mut rapier_config: ResMut<RapierConfiguration>) {
+    rapier_config.physics_pipeline_active = false;
+}
+
+fn resume_physics(mut rapier_config: ResMut<RapierConfiguration>) {
+    rapier_config.physics_pipeline_active = true;
+}
+
 fn reload_level(
     mut commands: Commands,
     level_query: Query<Entity, With<Handle<LdtkLevel>>>,
@@ -79,7 +111,7 @@ fn reload_level(
     }
 }
 
-fn switch_level(
+pub fn switch_level(
     mut level_selection: ResMut<LevelSelection>,
     mut change_event_listener: EventReader<ChangeLevelEvent>,
     mut next_state: ResMut<NextState<GameState>>,
@@ -117,9 +149,18 @@ fn test_switch_level(mut level_selection: ResMut<LevelSelection>, input: Res<Inp
     }
 }
 
-fn setup_world(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn setup_world(
+    mut commands: Commands,
+    level_assets: Res<LevelAssets>,
+    world_query: Query<(), With<Handle<LdtkAsset>>>,
+) {
+    // Coming back from the pause menu, the world is still loaded
+    if !world_query.is_empty() {
+        return;
+    }
+
     commands.spawn(LdtkWorldBundle {
-        ldtk_handle: asset_server.load("levels/World.ldtk"),
+        ldtk_handle: level_assets.world.clone(),
         ..Default::default()
     });
 }
@@ -138,7 +179,7 @@ pub struct SpikeBundle {
     pub label: Labeled,
     pub active_events: ActiveEvents,
     pub rotation_constraints: LockedAxes,
-    pub death: crate::player::TouchDeath,
+    pub hazard: crate::health::Hazard,
 }
 
 impl LdtkIntCell for SpikeBundle {
@@ -152,22 +193,15 @@ impl LdtkIntCell for SpikeBundle {
             },
             rotation_constraints,
             active_events: ActiveEvents::COLLISION_EVENTS,
-            ..Default::default()
+            // Spikes kill on touch, whatever the victim's health
+            hazard: crate::health::Hazard {
+                damage: u32::MAX,
+                ..Default::default()
+            },
         }
     }
 }
 
-#[derive(Clone, Default, Bundle, LdtkEntity)]
-pub struct WheatBundle {
-    pub collider: Collider,
-    pub label: Labeled,
-    pub sensor: Sensor,
-    pub active_events: ActiveEvents,
-    pub rotation_constraints: LockedAxes,
-    #[sprite_bundle("sprites/wheat_grown.png")]
-    pub sprite_bundle: SpriteBundle,
-}
-
 /// FRom bevy_ecs_ldtk platformer example
 /// Spawns rapier collisions for the walls of a level
 ///
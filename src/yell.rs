@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    actor::{actor_movement, Actor, ActorStatus},
+    fixed_step::SimulationSet,
+    player::player_inputs,
+    GameState,
+};
+
+pub struct YellPlugin;
+
+/// Lets an actor yell when its `Actor::yell_input` is set
+#[derive(Component, Clone, Debug)]
+pub struct Yeller {
+    /// How far away the yell is heard
+    pub radius: f32,
+    /// How long the yell lasts, another can't start before it ends
+    pub duration: f32,
+}
+
+impl Default for Yeller {
+    fn default() -> Self {
+        Yeller {
+            radius: 96.,
+            duration: 0.5,
+        }
+    }
+}
+
+/// Sent once when a yell starts, for anything that reacts to being yelled at
+#[derive(Event, Clone, Debug)]
+pub struct YellEvent {
+    pub source: Entity,
+    pub position: Vec2,
+    pub radius: f32,
+    /// Every other collider within the radius
+    pub heard_by: Vec<Entity>,
+}
+
+impl Plugin for YellPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<YellEvent>()
+            .add_systems(FixedUpdate, actor_yell
+                .after(player_inputs)
+                .before(actor_movement)
+                .in_set(SimulationSet)
+                .run_if(in_state(GameState::Playing))
+            );
+    }
+}
+
+fn actor_yell(
+    fixed_time: Res<FixedTime>,
+    rapier_context: Res<RapierContext>,
+    mut yell_writer: EventWriter<YellEvent>,
+    mut yeller_query: Query<(Entity, &Transform, &Actor, &Yeller, &mut ActorStatus)>,
+) {
+    let dt = fixed_time.period.as_secs_f32();
+
+    for (entity, transform, actor, yeller, mut status) in &mut yeller_query {
+        if status.yelling > 0. {
+            status.yelling = (status.yelling - dt).max(0.);
+            continue;
+        }
+
+        if !actor.yell_input {
+            continue;
+        }
+
+        status.yelling = yeller.duration;
+
+        let shape = Collider::ball(yeller.radius);
+        let filter = QueryFilter::new().exclude_collider(entity);
+        let shape_pos = transform.translation.truncate();
+
+        let mut heard_by = Vec::new();
+        rapier_context.intersections_with_shape(shape_pos, 0., &shape, filter, |heard| -> bool {
+            heard_by.push(heard);
+            true
+        });
+
+        yell_writer.send(YellEvent {
+            source: entity,
+            position: shape_pos,
+            radius: yeller.radius,
+            heard_by,
+        });
+    }
+}
@@ -0,0 +1,43 @@
+use platformer_game::{LevelRecord, SaveData};
+
+/// A slot written by the first release of save files
+const SAVE_V1: &str = include_str!("fixtures/save_v1.ron");
+
+#[test]
+fn version_one_saves_are_upgraded() {
+    let data = SaveData::from_ron(SAVE_V1).expect("the version 1 save could not be read");
+
+    assert_eq!(data.version, 2);
+    assert_eq!(data.current_level, 2);
+    assert_eq!(
+        data.levels[&0],
+        LevelRecord {
+            unlocked: true,
+            completed: true,
+            souls: Some(3),
+            best_time: Some(41.5),
+        }
+    );
+    // A clear without any souls is still remembered as one
+    assert_eq!(data.levels[&1].souls, Some(0));
+    assert_eq!(
+        data.levels[&2],
+        LevelRecord {
+            unlocked: true,
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn upgraded_saves_are_written_as_the_current_version() {
+    let data = SaveData::from_ron(SAVE_V1).unwrap();
+    let text = data.to_ron().unwrap();
+
+    assert_eq!(SaveData::from_ron(&text).unwrap(), data);
+}
+
+#[test]
+fn saves_without_a_version_are_rejected() {
+    assert!(SaveData::from_ron("(current_level: 1)").is_err());
+}
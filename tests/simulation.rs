@@ -0,0 +1,898 @@
+use bevy::prelude::*;
+use platformer_game::harness::SimulationHarness;
+use platformer_game::{
+    ActorEvent, Behaviour, EnemyType, JumpState, PickupType, PlayerActions, PropType,
+};
+
+const WORLD: &str = "levels/World.ldtk";
+
+/// The first level, with the player already standing on the ground
+fn landed() -> SimulationHarness {
+    let mut harness = SimulationHarness::new(WORLD, 0);
+    harness
+        .run_until(180, |harness| harness.player_status(0).grounded)
+        .expect("the player never landed");
+    harness
+}
+
+#[test]
+fn player_lands_after_spawning() {
+    let mut harness = landed();
+
+    let status = harness.player_status(0);
+    assert_eq!(status.jump_state, JumpState::Grounded);
+    assert_eq!(status.velocity.y, 0.);
+}
+
+#[test]
+fn holding_right_moves_the_player_right() {
+    let mut harness = landed();
+    let start = harness.player_position(0);
+
+    harness.run_script(
+        0,
+        &[(30, PlayerActions {
+            movement: Vec2::X,
+            ..default()
+        })],
+    );
+
+    assert!(harness.player_position(0).x > start.x + 10.);
+    assert!(!harness.player_status(0).facing_left);
+}
+
+#[test]
+fn jumping_leaves_the_ground_and_lands_again() {
+    let mut harness = landed();
+    let start = harness.player_position(0);
+
+    harness.run_script(
+        0,
+        &[
+            (1, PlayerActions {
+                jump: true,
+                jump_pressed: true,
+                ..default()
+            }),
+            (5, PlayerActions {
+                jump: true,
+                ..default()
+            }),
+        ],
+    );
+
+    let status = harness.player_status(0);
+    assert!(!status.grounded);
+    assert!(matches!(status.jump_state, JumpState::Ascending(_)));
+    assert!(harness.player_position(0).y > start.y);
+
+    harness.set_actions(0, PlayerActions::default());
+    harness
+        .run_until(180, |harness| harness.player_status(0).grounded)
+        .expect("the player never came back down");
+    let tick = harness.ticks();
+    assert!(harness.player_events(0).contains(&(tick, ActorEvent::Landed)));
+}
+
+#[test]
+fn touching_an_open_door_completes_the_level() {
+    let mut harness = landed();
+    let position = harness.player_position(0);
+    harness.spawn_door(position, 1);
+
+    harness
+        .run_until(10, |harness| !harness.level_changes().is_empty())
+        .expect("the door was never reached");
+
+    let (_, change) = &harness.level_changes()[0];
+    assert_eq!(change.index, 1);
+    assert!(change.completed);
+    let (tick, _) = harness.level_changes()[0];
+    assert!(harness.player_events(0).contains(&(tick, ActorEvent::Win)));
+}
+
+#[test]
+fn doors_open_at_their_own_soul_counts() {
+    let mut harness = landed();
+    let position = harness.player_position(0);
+    // Only the door needing two souls is in reach
+    harness.spawn_locked_door(position + Vec2::new(0., 80.), 1, 1);
+    harness.spawn_locked_door(position, 2, 2);
+
+    let unlocks = |harness: &mut SimulationHarness| {
+        harness
+            .player_events(0)
+            .iter()
+            .filter(|(_, event)| *event == ActorEvent::Unlock)
+            .count()
+    };
+
+    harness.spawn_pickup(PickupType::Soul, position);
+    harness.run(10);
+    assert_eq!(unlocks(&mut harness), 1);
+    assert!(harness.level_changes().is_empty());
+
+    harness.spawn_pickup(PickupType::Soul, position);
+    harness
+        .run_until(10, |harness| !harness.level_changes().is_empty())
+        .expect("the second door never opened");
+    assert_eq!(unlocks(&mut harness), 2);
+    assert_eq!(harness.level_changes()[0].1.index, 2);
+}
+
+#[test]
+fn a_soul_touched_by_two_players_counts_once() {
+    let mut harness = two_players_landed();
+    let first = harness.player_position(0);
+    let second = harness.player_position(1);
+    assert!((first - second).length() < 8.);
+
+    harness.spawn_pickup(PickupType::Soul, (first + second) / 2.);
+    harness.run(10);
+    assert_eq!(harness.souls_collected(), 1);
+}
+
+#[test]
+fn touching_spikes_kills_the_player() {
+    let mut harness = landed();
+    let spikes = harness.hazard_positions();
+    assert!(!spikes.is_empty(), "the first level has no spikes");
+
+    harness.set_player_position(0, spikes[0]);
+    harness
+        .run_until(10, |harness| harness.player_health(0).is_dead())
+        .expect("the spikes never killed the player");
+
+    harness.tick();
+    assert!(harness.player_dying(0));
+}
+
+#[test]
+fn touching_a_hazard_hurts_and_knocks_back() {
+    let mut harness = landed();
+    let position = harness.player_position(0);
+    harness.spawn_hazard(position + Vec2::new(4., 0.), 1);
+
+    let full_health = harness.player_health(0);
+    harness
+        .run_until(10, |harness| {
+            harness.player_events(0).iter().any(|(_, event)| *event == ActorEvent::Hit)
+        })
+        .expect("the hazard never hurt the player");
+
+    let health = harness.player_health(0);
+    assert_eq!(health.current, full_health.current - 1);
+    assert!(health.is_invulnerable());
+
+    harness.tick();
+    let status = harness.player_status(0);
+    assert!(status.velocity.y > 0.);
+    assert!(status.velocity.x < 0.);
+    assert!(harness.reloads().is_empty());
+}
+
+#[test]
+fn running_out_of_health_respawns_the_player() {
+    let mut harness = SimulationHarness::new(WORLD, 0);
+    let spawn = harness.player_position(0);
+    harness
+        .run_until(180, |harness| harness.player_status(0).grounded)
+        .expect("the player never landed");
+
+    // Far enough above the spawn to be out of reach once respawned
+    let hazard = spawn + Vec2::new(0., 48.);
+    harness.spawn_hazard(hazard, 1);
+    let hits = harness.player_health(0).current;
+
+    for _ in 0..hits {
+        // Wait out the invulnerability from the previous hit before going back in
+        harness
+            .run_until(180, |harness| !harness.player_health(0).is_invulnerable())
+            .expect("the invulnerability never wore off");
+        harness.set_player_position(0, hazard);
+        harness
+            .run_until(10, |harness| harness.player_health(0).is_invulnerable())
+            .expect("the hazard never hurt the player");
+    }
+
+    assert!(harness.player_health(0).is_dead());
+    // Every hit is presented once, and the last one as the death
+    let damage: Vec<ActorEvent> = harness
+        .player_events(0)
+        .into_iter()
+        .map(|(_, event)| event)
+        .filter(|event| matches!(event, ActorEvent::Hit | ActorEvent::Died))
+        .collect();
+    assert_eq!(damage.len(), hits as usize);
+    assert_eq!(damage.last(), Some(&ActorEvent::Died));
+
+    harness.tick();
+    assert!(harness.player_dying(0));
+
+    harness
+        .run_until(120, |harness| !harness.player_dying(0))
+        .expect("the player never respawned");
+
+    let health = harness.player_health(0);
+    assert_eq!(health.current, health.max);
+    assert!(health.is_invulnerable());
+    assert!((harness.player_position(0) - spawn).length() < 4.);
+    assert!(harness.reloads().is_empty());
+    assert!(harness.level_changes().is_empty());
+}
+
+#[test]
+fn dying_player_respawns_at_the_last_checkpoint() {
+    let mut harness = landed();
+    let checkpoint = harness.player_position(0) + Vec2::new(0., 8.);
+    harness.spawn_checkpoint(checkpoint);
+    harness.run(5);
+
+    harness.run_script(
+        0,
+        &[(30, PlayerActions {
+            movement: Vec2::X,
+            ..default()
+        })],
+    );
+    assert!(harness.player_position(0).x > checkpoint.x + 10.);
+
+    harness.hurt_player(0, u32::MAX);
+    harness
+        .run_until(120, |harness| harness.player_health(0).is_dead())
+        .expect("the damage never landed");
+    harness
+        .run_until(120, |harness| !harness.player_health(0).is_dead())
+        .expect("the player never respawned");
+
+    assert!((harness.player_position(0) - checkpoint).length() < 4.);
+}
+
+#[test]
+fn one_player_dying_leaves_the_other_alone() {
+    let mut harness = landed();
+    harness.join_player(1);
+    harness
+        .run_until(10, |harness| harness.player_entity(1).is_some())
+        .expect("the second player never spawned");
+    let second = harness.player_entity(1);
+
+    harness.hurt_player(0, u32::MAX);
+    harness.run(2);
+    assert!(harness.player_dying(0));
+
+    assert_eq!(harness.player_entity(1), second);
+    assert!(!harness.player_dying(1));
+    let health = harness.player_health(1);
+    assert_eq!(health.current, health.max);
+    assert!(harness.reloads().is_empty());
+}
+
+#[test]
+fn respawning_keeps_what_the_level_has_collected() {
+    let mut harness = landed();
+    let start = harness.player_position(0);
+    harness.spawn_pickup(PickupType::Soul, start);
+    harness
+        .run_until(10, |harness| harness.souls_collected() == 1)
+        .expect("the soul was never collected");
+
+    harness.spawn_pickup(PickupType::Coin, start + Vec2::new(0., 80.));
+    harness.run(2);
+    let pickups = harness.pickup_count();
+
+    harness.hurt_player(0, u32::MAX);
+    harness
+        .run_until(120, |harness| harness.player_health(0).is_dead())
+        .expect("the damage never landed");
+    harness
+        .run_until(120, |harness| !harness.player_health(0).is_dead())
+        .expect("the player never respawned");
+
+    assert_eq!(harness.souls_collected(), 1);
+    assert_eq!(harness.pickup_count(), pickups);
+    assert!(harness.reloads().is_empty());
+}
+
+#[test]
+fn players_joining_mid_jump_start_at_the_spawn() {
+    let mut harness = landed();
+    let start = harness.player_position(0);
+
+    // Wherever the first player is right now shouldn't matter
+    harness.set_player_position(0, start + Vec2::new(40., 60.));
+    harness.tick();
+    harness.join_player(1);
+    harness
+        .run_until(10, |harness| harness.player_entity(1).is_some())
+        .expect("the second player never spawned");
+
+    // Spawned above where the first player landed, not next to them in the air
+    let joined = harness.player_position(1);
+    assert!((joined.x - start.x).abs() < 1.);
+    assert!(joined.y > start.y - 1.);
+}
+
+#[test]
+fn players_placed_by_the_level_stay_without_a_gamepad() {
+    let mut harness = landed();
+    let start = harness.player_position(0);
+    let placed = harness.spawn_player(1, start + Vec2::new(16., 0.));
+    harness.run(10);
+    assert!(harness.exists(placed));
+
+    // A gamepad joining and leaving again only takes away players it brought
+    harness.join_player(2);
+    harness
+        .run_until(10, |harness| harness.player_entity(2).is_some())
+        .expect("the third player never spawned");
+    harness.leave_player(2);
+    harness
+        .run_until(10, |harness| harness.player_entity(2).is_none())
+        .expect("the third player was never removed");
+    assert!(harness.exists(placed));
+}
+
+/// Two players standing together on the first level
+fn two_players_landed() -> SimulationHarness {
+    let mut harness = landed();
+    harness.join_player(1);
+    harness
+        .run_until(10, |harness| harness.player_entity(1).is_some())
+        .expect("the second player never spawned");
+    harness
+        .run_until(180, |harness| harness.player_status(1).grounded)
+        .expect("the second player never landed");
+    harness
+}
+
+/// Two players on the first level, the first one holding the second overhead
+fn carrying_second_player() -> SimulationHarness {
+    let mut harness = two_players_landed();
+
+    harness.run_script(
+        0,
+        &[
+            (1, PlayerActions {
+                action: true,
+                ..default()
+            }),
+            (5, PlayerActions::default()),
+        ],
+    );
+    assert!(harness.player_status(0).carrying);
+    assert!(harness.player_status(1).carried);
+    harness
+}
+
+#[test]
+fn grabbing_while_standing_still_drops_in_place() {
+    let mut harness = carrying_second_player();
+    let start = harness.player_position(1);
+
+    harness.run_script(
+        0,
+        &[
+            (1, PlayerActions {
+                action: true,
+                ..default()
+            }),
+            (10, PlayerActions::default()),
+        ],
+    );
+
+    let status = harness.player_status(1);
+    assert!(!status.carried);
+    assert!(!status.thrown);
+    assert!((harness.player_position(1).x - start.x).abs() < 1.);
+}
+
+#[test]
+fn grabbing_with_a_direction_held_throws() {
+    let mut harness = carrying_second_player();
+    let start = harness.player_position(1);
+
+    harness.run_script(
+        0,
+        &[(1, PlayerActions {
+            movement: Vec2::X,
+            action: true,
+            ..default()
+        })],
+    );
+    harness.set_actions(0, PlayerActions::default());
+    harness.run(2);
+
+    let status = harness.player_status(1);
+    assert!(!status.carried);
+    assert!(status.thrown);
+    assert!(status.velocity.x > 0.);
+    assert!(status.velocity.y > 0.);
+
+    harness.run(10);
+    assert!(harness.player_position(1).x > start.x + 20.);
+    assert!(harness.player_position(1).x > harness.player_position(0).x + 20.);
+}
+
+#[test]
+fn throws_go_the_way_the_direction_is_held() {
+    let mut harness = carrying_second_player();
+    let start = harness.player_position(1);
+
+    // Facing right from the start, but thrown to the left
+    assert!(!harness.player_status(0).facing_left);
+    harness.run_script(
+        0,
+        &[(1, PlayerActions {
+            movement: Vec2::NEG_X,
+            action: true,
+            ..default()
+        })],
+    );
+    harness.set_actions(0, PlayerActions::default());
+    harness.run(2);
+
+    let status = harness.player_status(1);
+    assert!(status.thrown);
+    assert!(status.velocity.x < 0.);
+
+    harness.run(10);
+    assert!(harness.player_position(1).x < start.x - 20.);
+}
+
+#[test]
+fn crates_fall_and_can_be_stood_on() {
+    let mut harness = landed();
+    let floor = harness.player_position(0);
+    let crate_entity = harness.spawn_prop(PropType::Crate, floor + Vec2::new(24., 32.));
+
+    harness
+        .run_until(120, |harness| harness.status(crate_entity).grounded)
+        .expect("the crate never landed");
+    harness.run(2);
+
+    // Props and players are both as tall as the gap between their center and the floor
+    let resting = harness.position(crate_entity);
+    assert!((resting.y - floor.y).abs() < 2.);
+
+    harness.set_player_position(0, resting + Vec2::new(0., 24.));
+    harness
+        .run_until(120, |harness| harness.player_status(0).grounded)
+        .expect("the player never landed on the crate");
+    assert!(harness.player_position(0).y > resting.y + 12.);
+}
+
+#[test]
+fn crates_dropped_on_crates_stack_up() {
+    let mut harness = landed();
+    let floor = harness.player_position(0);
+    let bottom = harness.spawn_prop(PropType::Crate, floor + Vec2::new(24., 0.));
+    let top = harness.spawn_prop(PropType::Crate, floor + Vec2::new(24., 48.));
+
+    harness
+        .run_until(120, |harness| harness.status(top).grounded)
+        .expect("the top crate never landed");
+    harness.run(2);
+
+    // Crates are 16 across, so the top one rests a whole crate higher
+    let resting = harness.position(top);
+    assert!((resting.y - (harness.position(bottom).y + 16.)).abs() < 2.);
+}
+
+#[test]
+fn carried_crates_are_thrown_and_land() {
+    let mut harness = landed();
+    let start = harness.player_position(0);
+    let crate_entity = harness.spawn_prop(PropType::Crate, start + Vec2::new(4., 0.));
+    harness.run(2);
+
+    harness.run_script(
+        0,
+        &[
+            (1, PlayerActions {
+                action: true,
+                ..default()
+            }),
+            (5, PlayerActions::default()),
+        ],
+    );
+    assert!(harness.player_status(0).carrying);
+    assert!(harness.position(crate_entity).y > start.y + 8.);
+
+    harness.run_script(
+        0,
+        &[
+            (1, PlayerActions {
+                movement: Vec2::X,
+                action: true,
+                ..default()
+            }),
+            (1, PlayerActions::default()),
+        ],
+    );
+    harness
+        .run_until(120, |harness| harness.status(crate_entity).grounded)
+        .expect("the thrown crate never landed");
+    assert!(harness.position(crate_entity).x > start.x + 20.);
+}
+
+#[test]
+fn carried_players_can_carry_a_stack() {
+    let mut harness = carrying_second_player();
+    harness.join_player(2);
+    harness
+        .run_until(10, |harness| harness.player_entity(2).is_some())
+        .expect("the third player never spawned");
+    harness.run(2);
+
+    // Lift the third player to where the second one is held so it can reach
+    let held = harness.player_position(1);
+    harness.set_player_position(2, held);
+    harness.tick();
+    harness.run_script(
+        1,
+        &[
+            (1, PlayerActions {
+                action: true,
+                ..default()
+            }),
+            (5, PlayerActions::default()),
+        ],
+    );
+
+    let bottom = harness.player_position(0);
+    assert!(harness.player_status(1).carrying);
+    assert!(harness.player_status(2).carried);
+    assert!((harness.player_position(2).y - (bottom.y + 32.)).abs() < 1.);
+
+    // Jumping off from the middle takes the top of the stack along
+    let middle = harness.player_position(1);
+    harness.run_script(
+        1,
+        &[
+            (1, PlayerActions {
+                jump: true,
+                jump_pressed: true,
+                ..default()
+            }),
+            (3, PlayerActions::default()),
+        ],
+    );
+
+    assert!(!harness.player_status(0).carrying);
+    assert!(!harness.player_status(1).carried);
+    assert!(harness.player_position(1).y > middle.y + 1.);
+    assert!(harness.player_status(1).carrying);
+    assert!(harness.player_status(2).carried);
+}
+
+#[test]
+fn grabbing_each_other_at_once_makes_no_loop() {
+    let mut harness = two_players_landed();
+
+    let grab = PlayerActions {
+        action: true,
+        ..default()
+    };
+    harness.set_actions(0, grab);
+    harness.set_actions(1, grab);
+    harness.tick();
+    harness.set_actions(0, PlayerActions::default());
+    harness.set_actions(1, PlayerActions::default());
+    harness.run(5);
+
+    let first = harness.player_status(0);
+    let second = harness.player_status(1);
+    assert!(first.carrying != second.carrying);
+    assert!(first.carried != second.carried);
+    assert_eq!(first.carrying, second.carried);
+}
+
+#[test]
+fn walking_into_a_block_pushes_it_slowly() {
+    let mut harness = landed();
+    let start = harness.player_position(0);
+    let block = harness.spawn_block(start + Vec2::new(14., 0.));
+    harness.run(2);
+    let block_start = harness.position(block);
+
+    harness.run_script(
+        0,
+        &[(60, PlayerActions {
+            movement: Vec2::X,
+            ..default()
+        })],
+    );
+
+    let status = harness.player_status(0);
+    assert!(status.pushing.is_some());
+    let moved = harness.position(block).x - block_start.x;
+    assert!(moved > 10.);
+    // A second of walking would have covered far more ground without the block
+    assert!(harness.player_position(0).x - start.x < 60.);
+
+    harness.set_actions(0, PlayerActions::default());
+    harness.run(5);
+    let stopped = harness.position(block).x;
+    harness.run(10);
+    assert!((harness.position(block).x - stopped).abs() < 0.5);
+}
+
+#[test]
+fn blocks_pushed_off_a_ledge_fall_to_the_floor() {
+    let mut harness = landed();
+    let floor = harness.player_position(0);
+
+    // A ledge two crates wide, the player on one and the block on the other
+    harness.spawn_prop(PropType::Crate, floor + Vec2::new(24., 0.));
+    let ledge = harness.spawn_prop(PropType::Crate, floor + Vec2::new(40., 0.));
+    let block = harness.spawn_block(floor + Vec2::new(40., 16.));
+    harness.set_player_position(0, floor + Vec2::new(24., 16.));
+    harness.run(10);
+    assert!(harness.status(block).grounded);
+    assert!(harness.player_status(0).grounded);
+
+    harness.set_actions(
+        0,
+        PlayerActions {
+            movement: Vec2::X,
+            ..default()
+        },
+    );
+    harness
+        .run_until(240, |harness| {
+            harness.status(block).grounded && harness.position(block).y < floor.y + 2.
+        })
+        .expect("the block never fell off the ledge");
+    assert!(harness.position(block).x > harness.position(ledge).x + 16.);
+}
+
+#[test]
+fn yelling_is_heard_by_players_nearby() {
+    let mut harness = landed();
+    harness.join_player(1);
+    harness
+        .run_until(10, |harness| harness.player_entity(1).is_some())
+        .expect("the second player never spawned");
+    harness.run(2);
+
+    let yell = PlayerActions {
+        yell: true,
+        ..default()
+    };
+    harness.run_script(0, &[(1, yell), (1, PlayerActions::default())]);
+
+    assert_eq!(harness.yells().len(), 1);
+    let (_, ev) = &harness.yells()[0];
+    assert_eq!(Some(ev.source), harness.player_entity(0));
+    assert!(ev.heard_by.contains(&harness.player_entity(1).unwrap()));
+    assert!(!ev.heard_by.contains(&ev.source));
+    assert!(harness.player_status(0).yelling > 0.);
+
+    // No second yell until the first one is over
+    harness.run_script(0, &[(1, yell), (1, PlayerActions::default())]);
+    assert_eq!(harness.yells().len(), 1);
+}
+
+#[test]
+fn yells_are_not_heard_out_of_range() {
+    let mut harness = landed();
+    let start = harness.player_position(0);
+    harness.join_player(1);
+    harness
+        .run_until(10, |harness| harness.player_entity(1).is_some())
+        .expect("the second player never spawned");
+
+    // Well past the 96 pixel yell radius
+    harness.set_player_position(1, start + Vec2::new(0., 160.));
+    harness.run_script(
+        0,
+        &[
+            (1, PlayerActions {
+                yell: true,
+                ..default()
+            }),
+            (1, PlayerActions::default()),
+        ],
+    );
+
+    assert_eq!(harness.yells().len(), 1);
+    let (_, ev) = &harness.yells()[0];
+    assert!(!ev.heard_by.contains(&harness.player_entity(1).unwrap()));
+}
+
+#[test]
+fn attacking_wheat_cuts_it_and_it_grows_back() {
+    let mut harness = landed();
+    let start = harness.player_position(0);
+    let wheat = harness.spawn_wheat(start + Vec2::new(14., 0.));
+    harness.run(2);
+    let pickups = harness.pickup_count();
+
+    let attack = PlayerActions {
+        attack: true,
+        ..default()
+    };
+    harness.run_script(0, &[(1, attack), (1, PlayerActions::default())]);
+
+    assert!(harness.is_cut(wheat));
+    assert_eq!(harness.pickup_count(), pickups + 1);
+
+    // Cut wheat can't be cut again for another drop
+    harness.run(30);
+    harness.run_script(0, &[(1, attack), (1, PlayerActions::default())]);
+    assert_eq!(harness.pickup_count(), pickups + 1);
+
+    harness
+        .run_until(60 * 10, |harness| !harness.is_cut(wheat))
+        .expect("the wheat never grew back");
+}
+
+#[test]
+fn attacks_only_reach_the_way_the_player_faces() {
+    let mut harness = landed();
+    let start = harness.player_position(0);
+    let wheat = harness.spawn_wheat(start - Vec2::new(14., 0.));
+    harness.run(2);
+
+    let attack = PlayerActions {
+        attack: true,
+        ..default()
+    };
+    harness.run_script(0, &[(1, attack), (20, PlayerActions::default())]);
+    assert!(!harness.is_cut(wheat));
+}
+
+#[test]
+fn patrolling_enemies_turn_around_at_walls() {
+    let mut harness = landed();
+    let start = harness.player_position(0);
+    harness.spawn_prop(PropType::Crate, start - Vec2::new(32., 0.));
+    let enemy = harness.spawn_enemy(EnemyType::Sam, Behaviour::Patrol, start);
+    harness.run(2);
+
+    // Enemies start out heading left
+    assert!(harness.status(enemy).facing_left);
+    harness
+        .run_until(120, |harness| !harness.status(enemy).facing_left)
+        .expect("the enemy never turned around");
+    assert!(harness.position(enemy).x > start.x - 24.);
+}
+
+#[test]
+fn patrolling_enemies_turn_around_at_ledges() {
+    let mut harness = landed();
+    let floor = harness.player_position(0);
+    harness.spawn_prop(PropType::Crate, floor + Vec2::new(24., 0.));
+    harness.spawn_prop(PropType::Crate, floor + Vec2::new(40., 0.));
+    let enemy = harness.spawn_enemy(EnemyType::Sam, Behaviour::Patrol, floor + Vec2::new(32., 16.));
+    harness
+        .run_until(60, |harness| harness.status(enemy).grounded)
+        .expect("the enemy never landed on the crates");
+
+    // Heads left, turns at the edge, and walks back across without ever stepping off
+    harness
+        .run_until(120, |harness| !harness.status(enemy).facing_left)
+        .expect("the enemy never turned around");
+    harness
+        .run_until(120, |harness| harness.status(enemy).facing_left)
+        .expect("the enemy never turned back");
+    assert!(harness.position(enemy).y > floor.y + 8.);
+}
+
+#[test]
+fn attacking_an_enemy_twice_defeats_it() {
+    let mut harness = landed();
+    let start = harness.player_position(0);
+    let enemy = harness.spawn_enemy(EnemyType::Dampboi, Behaviour::Hop, start + Vec2::new(14., 0.));
+    harness.run(2);
+
+    let attack = PlayerActions {
+        attack: true,
+        ..default()
+    };
+    harness.run_script(0, &[(1, attack), (30, PlayerActions::default())]);
+    assert_eq!(harness.health(enemy).unwrap().current, 1);
+    assert!(harness.position(enemy).x > start.x + 14.);
+
+    let knocked_to = harness.position(enemy);
+    harness.set_player_position(0, knocked_to - Vec2::new(14., 0.));
+    harness.tick();
+    harness.run_script(0, &[(1, attack), (1, PlayerActions::default())]);
+
+    harness
+        .run_until(60, |harness| !harness.exists(enemy))
+        .expect("the defeated enemy was never removed");
+}
+
+#[test]
+fn landing_on_an_enemy_defeats_it_and_bounces() {
+    let mut harness = landed();
+    let start = harness.player_position(0);
+    harness.set_player_position(0, start + Vec2::new(0., 40.));
+    let enemy = harness.spawn_enemy(EnemyType::Dampboi, Behaviour::Hop, start);
+    harness.tick();
+
+    harness
+        .run_until(60, |harness| harness.player_status(0).velocity.y > 0.)
+        .expect("the player never bounced off the enemy");
+    assert!(harness.health(enemy).unwrap().is_dead());
+
+    let health = harness.player_health(0);
+    assert_eq!(health.current, health.max);
+
+    harness
+        .run_until(60, |harness| !harness.exists(enemy))
+        .expect("the stomped enemy was never removed");
+}
+
+#[test]
+fn stomping_an_enemy_stops_the_action_for_a_moment() {
+    let mut harness = landed();
+    let start = harness.player_position(0);
+    harness.set_player_position(0, start + Vec2::new(0., 40.));
+    let enemy = harness.spawn_enemy(EnemyType::Dampboi, Behaviour::Hop, start);
+    harness.tick();
+
+    let stomped = |harness: &mut SimulationHarness| {
+        harness
+            .player_events(0)
+            .iter()
+            .any(|(_, event)| *event == ActorEvent::Launched)
+    };
+    harness
+        .run_until(60, stomped)
+        .expect("the player never landed on the enemy");
+
+    // Nothing moves during the hit-stop, the bounce only starts after it
+    let player = harness.player_position(0);
+    let enemy_position = harness.position(enemy);
+    harness.run(4);
+    assert_eq!(harness.player_position(0), player);
+    assert_eq!(harness.position(enemy), enemy_position);
+
+    harness.run(4);
+    assert!(harness.player_position(0).y > player.y);
+}
+
+#[test]
+fn landing_on_sam_hurts_the_player() {
+    let mut harness = landed();
+    let start = harness.player_position(0);
+    harness.set_player_position(0, start + Vec2::new(0., 24.));
+    let enemy = harness.spawn_enemy(EnemyType::Sam, Behaviour::Hop, start);
+    harness.tick();
+
+    harness
+        .run_until(60, |harness| {
+            let health = harness.player_health(0);
+            health.current < health.max
+        })
+        .expect("landing on Sam never hurt the player");
+
+    assert!(!harness.health(enemy).unwrap().is_dead());
+    assert!(!harness
+        .player_events(0)
+        .iter()
+        .any(|(_, event)| *event == ActorEvent::Launched));
+}
+
+#[test]
+fn walking_into_an_enemy_hurts_the_player() {
+    let mut harness = landed();
+    let start = harness.player_position(0);
+    let enemy = harness.spawn_enemy(EnemyType::Sam, Behaviour::Patrol, start + Vec2::new(24., 0.));
+    harness.run(2);
+
+    harness
+        .run_until(120, |harness| {
+            let health = harness.player_health(0);
+            health.current < health.max
+        })
+        .expect("the enemy never hurt the player");
+
+    // Knocked back away from the enemy, which is left standing
+    harness.run(10);
+    assert!(harness.player_position(0).x < start.x);
+    assert!(!harness.health(enemy).unwrap().is_dead());
+}